@@ -72,3 +72,6 @@ def evaluate_polynomial(evaluated: bytes, state: bytes) -> tuple[float, set[int]
 def evaluate_constraint(evaluated: bytes, state: bytes) -> tuple[bytes, set[int]]: ...
 def evaluate_instance(evaluated: bytes, state: bytes) -> tuple[bytes, set[int]]: ...
 def used_decision_variable_ids(function: bytes) -> set[int]: ...
+def required_atol_for_feasibility(evaluated: bytes) -> float: ...
+def constraint_violations(solution: bytes) -> dict[int, float]: ...
+def infeasible_constraints(solution: bytes) -> list[int]: ...
@@ -10,10 +10,21 @@ from .function_pb2 import Function as _Function
 from .quadratic_pb2 import Quadratic as _Quadratic
 from .polynomial_pb2 import Polynomial as _Polynomial, Monomial as _Monomial
 from .linear_pb2 import Linear as _Linear
-from .constraint_pb2 import Equality, Constraint as _Constraint
+from .constraint_pb2 import (
+    Equality,
+    Constraint as _Constraint,
+    EvaluatedConstraint as _EvaluatedConstraint,
+)
 from .decision_variables_pb2 import DecisionVariable as _DecisionVariable, Bound
 
-from .._ommx_rust import evaluate_instance, used_decision_variable_ids
+from .._ommx_rust import (
+    evaluate_constraint,
+    evaluate_instance,
+    used_decision_variable_ids,
+    required_atol_for_feasibility,
+    constraint_violations,
+    infeasible_constraints,
+)
 
 
 @dataclass
@@ -145,6 +156,28 @@ class Instance:
         return concat([df, parameters], axis=1).set_index("id")
 
     def evaluate(self, state: State) -> Solution:
+        """
+        Evaluate the objective and constraints with the given state.
+
+        Decision variables of the solution are copied from the instance,
+        and thus their metadata like names, subscripts, parameters, and descriptions are preserved.
+
+        .. doctest::
+
+            >>> from ommx.v1 import Instance, DecisionVariable, State
+            >>> x = DecisionVariable.binary(0, name="x", subscripts=[3], parameters={"p": "1"})
+            >>> instance = Instance.from_components(
+            ...     decision_variables=[x],
+            ...     objective=x,
+            ...     constraints=[],
+            ...     sense=Instance.MINIMIZE,
+            ... )
+            >>> solution = instance.evaluate(State(entries={0: 1}))
+            >>> v = solution.raw.decision_variables[0]
+            >>> v.name, list(v.subscripts), dict(v.parameters)
+            ('x', [3], {'p': '1'})
+
+        """
         out, _ = evaluate_instance(self.to_bytes(), state.SerializeToString())
         return Solution.from_bytes(out)
 
@@ -226,6 +259,53 @@ class Solution:
         df.columns = MultiIndex.from_product([df.columns, [""]])
         return concat([df, parameters], axis=1).set_index("id")
 
+    def constraint_violations(self) -> dict[int, float]:
+        """
+        Violation of each constraint, i.e. ``|f(x)|`` for ``f(x) = 0`` and ``max(f(x), 0)`` for ``f(x) <= 0``.
+
+        .. doctest::
+
+            >>> from ommx.v1 import Instance, DecisionVariable, State
+            >>> x = [DecisionVariable.binary(i) for i in range(3)]
+            >>> c0 = x[0] + x[1] <= 1
+            >>> c1 = x[1] + x[2] == 1
+            >>> instance = Instance.from_components(
+            ...     decision_variables=x,
+            ...     objective=sum(x),
+            ...     constraints=[c0, c1],
+            ...     sense=Instance.MAXIMIZE,
+            ... )
+            >>> solution = instance.evaluate(State(entries={0: 1, 1: 1, 2: 1}))
+            >>> violations = solution.constraint_violations()
+            >>> violations[c0.raw.id], violations[c1.raw.id]
+            (1.0, 1.0)
+
+        """
+        return constraint_violations(self.to_bytes())
+
+    def infeasible_constraints(self) -> list[int]:
+        """
+        IDs of constraints violated more than the tolerance used to determine ``feasible`` of the solution.
+
+        .. doctest::
+
+            >>> from ommx.v1 import Instance, DecisionVariable, State
+            >>> x = [DecisionVariable.binary(i) for i in range(3)]
+            >>> c0 = x[0] + x[1] <= 1
+            >>> c1 = x[1] + x[2] == 1
+            >>> instance = Instance.from_components(
+            ...     decision_variables=x,
+            ...     objective=sum(x),
+            ...     constraints=[c0, c1],
+            ...     sense=Instance.MAXIMIZE,
+            ... )
+            >>> solution = instance.evaluate(State(entries={0: 1, 1: 0, 2: 0}))
+            >>> solution.infeasible_constraints() == [c1.raw.id]
+            True
+
+        """
+        return infeasible_constraints(self.to_bytes())
+
 
 def _decision_variables(obj: _Instance | _Solution) -> DataFrame:
     decision_variables = obj.decision_variables
@@ -687,3 +767,60 @@ class Constraint:
             equality=equality,
         )
         Constraint._counter += 1
+
+    def evaluate(self, state: State, atol: float = 1e-6) -> EvaluatedConstraint:
+        """
+        Evaluate this constraint with the given state, and determine its feasibility within the absolute tolerance ``atol``.
+
+        The value ``f(x)`` of the constraint ``f(x) = 0`` or ``f(x) <= 0`` is stored in :py:attr:`EvaluatedConstraint.value`,
+        and :py:attr:`EvaluatedConstraint.feasible` is ``True`` if :py:attr:`EvaluatedConstraint.violation` is at most ``atol``.
+
+        .. doctest::
+
+            >>> from ommx.v1 import DecisionVariable, State
+            >>> x = DecisionVariable.integer(1)
+            >>> y = DecisionVariable.integer(2)
+            >>> c = x + 2 * y <= 3
+            >>> evaluated = c.evaluate(State(entries={1: 1, 2: 2}))
+            >>> evaluated.value, evaluated.violation, evaluated.feasible
+            (2.0, 2.0, False)
+            >>> c.evaluate(State(entries={1: 1, 2: 1})).feasible
+            True
+            >>> c.evaluate(State(entries={1: 1, 2: 1.5}), atol=1.0).feasible
+            True
+
+        """
+        out, _ = evaluate_constraint(
+            self.raw.SerializeToString(), state.SerializeToString()
+        )
+        evaluated = _EvaluatedConstraint()
+        evaluated.ParseFromString(out)
+        return EvaluatedConstraint(evaluated, atol)
+
+
+@dataclass
+class EvaluatedConstraint:
+    """
+    Idiomatic wrapper of ``ommx.v1.EvaluatedConstraint`` protobuf message with the tolerance used to determine the feasibility.
+    """
+
+    raw: _EvaluatedConstraint
+    """The raw protobuf message."""
+
+    atol: float = 1e-6
+    """The absolute tolerance used to determine :py:attr:`feasible`."""
+
+    @property
+    def value(self) -> float:
+        """The value ``f(x)`` of the constraint ``f(x) = 0`` or ``f(x) <= 0``."""
+        return self.raw.evaluated_value
+
+    @property
+    def violation(self) -> float:
+        """The violation ``|f(x)|`` for ``f(x) = 0`` and ``max(f(x), 0)`` for ``f(x) <= 0``, i.e. the minimal tolerance to regard this constraint as satisfied."""
+        return required_atol_for_feasibility(self.raw.SerializeToString())
+
+    @property
+    def feasible(self) -> bool:
+        """Whether :py:attr:`violation` is at most :py:attr:`atol`."""
+        return self.violation <= self.atol
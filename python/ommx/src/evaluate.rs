@@ -1,10 +1,13 @@
 use anyhow::Result;
 use ommx::{
-    v1::{Constraint, Function, Instance, Linear, Polynomial, Quadratic, State},
+    v1::{
+        Constraint, EvaluatedConstraint, Function, Instance, Linear, Polynomial, Quadratic,
+        Solution, State,
+    },
     Evaluate, Message,
 };
 use pyo3::{prelude::*, types::PyBytes};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 macro_rules! define_evaluate_function {
     ($evaluated:ty, $name:ident) => {
@@ -49,3 +52,21 @@ pub fn used_decision_variable_ids(function: &Bound<PyBytes>) -> BTreeSet<u64> {
     let function = Function::decode(function.as_bytes()).unwrap();
     function.used_decision_variable_ids()
 }
+
+#[pyfunction]
+pub fn required_atol_for_feasibility(evaluated: &Bound<PyBytes>) -> Result<f64> {
+    let evaluated = EvaluatedConstraint::decode(evaluated.as_bytes())?;
+    evaluated.required_atol_for_feasibility()
+}
+
+#[pyfunction]
+pub fn constraint_violations(solution: &Bound<PyBytes>) -> Result<BTreeMap<u64, f64>> {
+    let solution = Solution::decode(solution.as_bytes())?;
+    solution.constraint_violations()
+}
+
+#[pyfunction]
+pub fn infeasible_constraints(solution: &Bound<PyBytes>) -> Result<Vec<u64>> {
+    let solution = Solution::decode(solution.as_bytes())?;
+    solution.infeasible_constraints()
+}
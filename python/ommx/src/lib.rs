@@ -26,5 +26,8 @@ fn _ommx_rust(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(evaluate_constraint, m)?)?;
     m.add_function(wrap_pyfunction!(evaluate_instance, m)?)?;
     m.add_function(wrap_pyfunction!(used_decision_variable_ids, m)?)?;
+    m.add_function(wrap_pyfunction!(required_atol_for_feasibility, m)?)?;
+    m.add_function(wrap_pyfunction!(constraint_violations, m)?)?;
+    m.add_function(wrap_pyfunction!(infeasible_constraints, m)?)?;
     Ok(())
 }
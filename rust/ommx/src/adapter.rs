@@ -0,0 +1,171 @@
+//! Common interface of solver adapters
+//!
+//! Solver adapters implement [`SolverAdapter`] so that generic code can be written against the trait,
+//! and users can swap solvers without changing the rest of their code.
+//!
+//! Adapters should also honor the guidance stored in decision variables, i.e. [`DecisionVariable::solver_fix`](crate::v1::DecisionVariable::solver_fix)
+//! by passing [`solver_bounds`] to the solver, and [`DecisionVariable::solver_hint`](crate::v1::DecisionVariable::solver_hint) by [`solver_initial_state`].
+
+use crate::v1::{Bound, Instance, Solution, State};
+use anyhow::{ensure, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+/// Options common to solvers
+///
+/// All options are optional, and `None` means the default of each solver.
+/// Adapters should document how each option is translated, and which options are ignored by their solver.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveOptions {
+    /// Wall-clock time limit of the optimization
+    pub time_limit: Option<Duration>,
+    /// Relative gap between the primal and dual bounds to stop the optimization
+    pub relative_gap: Option<f64>,
+    /// Number of threads used by the solver
+    ///
+    /// If the solver does not support parallelism, or supports fewer threads than requested,
+    /// adapters should use the closest supported number and emit a warning by [`log::warn!`] instead of failing.
+    /// For example, SCIP should map this to `parallel/maxnthreads`, which takes effect only with its concurrent solver.
+    pub threads: Option<usize>,
+    /// Initial state used as a warm start
+    pub initial_state: Option<State>,
+    /// Seed of the randomization in the solver
+    ///
+    /// Runs with the same seed are reproducible for the same build of the solver and the same number of threads.
+    /// For example, SCIP should map this to `randomization/randomseedshift`.
+    pub random_seed: Option<i64>,
+}
+
+/// Solve an [`Instance`] with a solver
+pub trait SolverAdapter {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Solve the instance and returns the solution evaluated with the instance, see [`Evaluate`](crate::Evaluate)
+    fn solve(instance: &Instance, options: &SolveOptions) -> Result<Solution, Self::Error>;
+}
+
+/// Bounds of decision variables passed to solvers, where decision variables with [`DecisionVariable::solver_fix`](crate::v1::DecisionVariable::solver_fix) are bounded by `[v, v]`
+///
+/// The bound of each decision variable is [`DecisionVariable::effective_bound`](crate::v1::DecisionVariable::effective_bound),
+/// i.e. binary ones are bounded by `[0, 1]`. This fails if the fixed value is out of the bound.
+pub fn solver_bounds(instance: &Instance) -> Result<BTreeMap<u64, Bound>> {
+    let mut bounds = BTreeMap::new();
+    for v in &instance.decision_variables {
+        let mut bound = v.effective_bound();
+        if let Some(value) = v.solver_fix()? {
+            ensure!(
+                bound.lower <= value && value <= bound.upper,
+                "Fixed value {value} of decision variable ({}) is out of the bound [{}, {}]",
+                v.id,
+                bound.lower,
+                bound.upper
+            );
+            bound = Bound {
+                lower: value,
+                upper: value,
+            };
+        }
+        bounds.insert(v.id, bound);
+    }
+    Ok(bounds)
+}
+
+/// Initial state passed to solvers, consisting of [`DecisionVariable::solver_hint`](crate::v1::DecisionVariable::solver_hint) and [`SolveOptions::initial_state`]
+///
+/// Values in [`SolveOptions::initial_state`] take precedence over the hints. `None` is returned if neither exists.
+pub fn solver_initial_state(instance: &Instance, options: &SolveOptions) -> Result<Option<State>> {
+    let mut entries = HashMap::new();
+    for v in &instance.decision_variables {
+        if let Some(value) = v.solver_hint()? {
+            entries.insert(v.id, value);
+        }
+    }
+    if let Some(state) = &options.initial_state {
+        entries.extend(state.entries.iter().map(|(id, value)| (*id, *value)));
+    } else if entries.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(entries.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{decision_variable::Kind, DecisionVariable};
+    use maplit::{btreemap, hashmap};
+
+    fn variable(id: u64, kind: Kind, bound: Option<(f64, f64)>) -> DecisionVariable {
+        DecisionVariable {
+            id,
+            kind: kind as i32,
+            bound: bound.map(|(lower, upper)| Bound { lower, upper }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let mut fixed = variable(4, Kind::Integer, Some((0.0, 10.0)));
+        fixed.set_solver_fix(3.0);
+        let mut instance = Instance {
+            decision_variables: vec![
+                variable(0, Kind::Binary, None),
+                variable(1, Kind::Binary, Some((-1.0, 0.0))),
+                variable(2, Kind::Continuous, None),
+                variable(3, Kind::Integer, Some((-2.0, 5.0))),
+                fixed,
+            ],
+            ..Default::default()
+        };
+        let bound = |lower, upper| Bound { lower, upper };
+        assert_eq!(
+            solver_bounds(&instance).unwrap(),
+            btreemap! {
+                0 => bound(0.0, 1.0),
+                1 => bound(0.0, 0.0),
+                2 => bound(f64::NEG_INFINITY, f64::INFINITY),
+                3 => bound(-2.0, 5.0),
+                4 => bound(3.0, 3.0),
+            }
+        );
+
+        // Fixing out of the bound, including [0, 1] of binary variables, is an error
+        instance.decision_variables[0].set_solver_fix(2.0);
+        assert!(solver_bounds(&instance).is_err());
+    }
+
+    #[test]
+    fn initial_state() {
+        let mut hinted = variable(1, Kind::Continuous, None);
+        hinted.set_solver_hint(1.5);
+        let mut overridden = variable(2, Kind::Continuous, None);
+        overridden.set_solver_hint(2.5);
+        let instance = Instance {
+            decision_variables: vec![variable(0, Kind::Continuous, None), hinted, overridden],
+            ..Default::default()
+        };
+
+        let state = solver_initial_state(&instance, &SolveOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.entries, hashmap! { 1 => 1.5, 2 => 2.5 });
+
+        let options = SolveOptions {
+            initial_state: Some(hashmap! { 0 => 0.5, 2 => -1.0 }.into()),
+            ..Default::default()
+        };
+        let state = solver_initial_state(&instance, &options).unwrap().unwrap();
+        assert_eq!(state.entries, hashmap! { 0 => 0.5, 1 => 1.5, 2 => -1.0 });
+
+        let plain = Instance {
+            decision_variables: vec![variable(0, Kind::Continuous, None)],
+            ..Default::default()
+        };
+        assert_eq!(
+            solver_initial_state(&plain, &SolveOptions::default()).unwrap(),
+            None
+        );
+    }
+}
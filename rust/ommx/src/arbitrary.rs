@@ -1,4 +1,4 @@
-use crate::random::random_lp;
+use crate::random::random_instance;
 use proptest::prelude::*;
 use rand::SeedableRng;
 
@@ -28,11 +28,6 @@ impl Arbitrary for crate::v1::Instance {
         // The instance yielded from strategy must depends only on the parameter deterministically.
         // Thus we should not use `thread_rng` here.
         let mut rng = rand_xoshiro::Xoshiro256StarStar::seed_from_u64(0);
-        match parameter {
-            InstanceParameter::LP {
-                num_constraints,
-                num_variables,
-            } => Just(random_lp(&mut rng, num_variables, num_constraints)).boxed(),
-        }
+        Just(random_instance(&mut rng, &parameter)).boxed()
     }
 }
@@ -83,6 +83,19 @@ pub fn get_images() -> Result<Vec<ImageName>> {
         .collect()
 }
 
+/// Load all instances stored in the artifact of `image_name`, pulling it from the registry if it is not found locally.
+///
+/// Every instance layer is decoded by [`Artifact::get_instances`], and returned with its descriptor in the order of the layers.
+pub fn load_all(image_name: ImageName) -> Result<Vec<(Descriptor, v1::Instance)>> {
+    let path = image_dir(&image_name)?;
+    let mut artifact = if path.exists() {
+        Artifact::from_oci_dir(&path)?
+    } else {
+        Artifact::from_remote(image_name)?.pull()?
+    };
+    artifact.get_instances()
+}
+
 /// OMMX Artifact, an OCI Artifact of type [`application/org.ommx.v1.artifact`][media_types::v1_artifact]
 pub struct Artifact<Base: Image>(OciArtifact<Base>);
 
@@ -278,3 +291,32 @@ impl<Base: Image> Artifact<Base> {
         Ok(out)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::random_lp;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn load_all_local() -> Result<()> {
+        let image_name = ImageName::parse(&format!(
+            "ghcr.io/jij-inc/ommx/test_load_all:{}",
+            uuid::Uuid::new_v4()
+        ))?;
+        let mut rng = Xoshiro256StarStar::seed_from_u64(0);
+        let instances = vec![random_lp(&mut rng, 3, 2), random_lp(&mut rng, 4, 1)];
+        let mut builder = Builder::new(image_name.clone())?;
+        builder.add_instance(instances[0].clone(), InstanceAnnotations::default())?;
+        builder.add_solution(v1::State::default(), SolutionAnnotations::default())?;
+        builder.add_instance(instances[1].clone(), InstanceAnnotations::default())?;
+        builder.build()?;
+
+        let loaded = load_all(image_name.clone());
+        std::fs::remove_dir_all(image_dir(&image_name)?)?;
+        let loaded: Vec<v1::Instance> = loaded?.into_iter().map(|(_, instance)| instance).collect();
+        assert_eq!(loaded, instances);
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::v1::Bound;
+
+impl Bound {
+    /// Returns the fixed value if the bound is degenerated, i.e. `|upper - lower| <= atol`.
+    ///
+    /// Such a decision variable takes only a single value, and can be treated as a constant.
+    pub fn is_fixed(&self, atol: f64) -> Option<f64> {
+        if (self.upper - self.lower).abs() <= atol {
+            Some(self.lower)
+        } else {
+            None
+        }
+    }
+
+    /// The minimal absolute tolerance to regard `value` as in this bound, i.e. `0.0` if `lower <= value <= upper`
+    pub fn required_atol_to_contain(&self, value: f64) -> f64 {
+        (self.lower - value).max(value - self.upper).max(0.0)
+    }
+
+    /// Unbounded interval `(-inf, inf)`
+    pub(crate) fn unbounded() -> Self {
+        Self {
+            lower: f64::NEG_INFINITY,
+            upper: f64::INFINITY,
+        }
+    }
+
+    /// Interval sum `[a + c, b + d]` of `[a, b]` and `[c, d]`
+    pub(crate) fn interval_add(&self, other: &Self) -> Self {
+        Self {
+            lower: self.lower + other.lower,
+            upper: self.upper + other.upper,
+        }
+    }
+
+    /// Interval product of `[a, b]` and `[c, d]`, where `0 * inf` is regarded as `0`
+    pub(crate) fn interval_mul(&self, other: &Self) -> Self {
+        let mul = |a: f64, b: f64| if a == 0.0 || b == 0.0 { 0.0 } else { a * b };
+        let products = [
+            mul(self.lower, other.lower),
+            mul(self.lower, other.upper),
+            mul(self.upper, other.lower),
+            mul(self.upper, other.upper),
+        ];
+        Self {
+            lower: products.iter().cloned().fold(f64::INFINITY, f64::min),
+            upper: products.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    /// Interval of `x^exp` for `x` in this interval, which is tighter than the repeated product for even `exp`
+    pub(crate) fn interval_pow(&self, exp: i32) -> Self {
+        let (lower, upper) = (self.lower.powi(exp), self.upper.powi(exp));
+        if exp % 2 == 1 || self.lower >= 0.0 {
+            Self { lower, upper }
+        } else if self.upper <= 0.0 {
+            Self {
+                lower: upper,
+                upper: lower,
+            }
+        } else {
+            Self {
+                lower: 0.0,
+                upper: lower.max(upper),
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::v1::{Bound, Constraint, Equality, EvaluatedConstraint, Linear, Polynomial};
+use anyhow::{bail, ensure, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Classification of a constraint by the range of its function, see [`Constraint::classify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintClass {
+    /// Satisfied by any values in the bounds, and thus can be removed
+    TriviallySatisfied,
+    /// Violated by any values in the bounds, and thus the problem is infeasible
+    TriviallyInfeasible,
+    /// Neither of above
+    Active,
+}
+
+impl Constraint {
+    /// Classify by the range of the function evaluated with [`Function::evaluate_interval`](crate::v1::Function::evaluate_interval) on `bounds`
+    ///
+    /// Decision variables not in `bounds` are regarded as unbounded. Since the range may not be tight,
+    /// a constraint classified as [`ConstraintClass::Active`] may still be trivial.
+    pub fn classify(&self, bounds: &BTreeMap<u64, Bound>, atol: f64) -> Result<ConstraintClass> {
+        let range = self
+            .function
+            .as_ref()
+            .with_context(|| format!("Function of constraint ({}) is not set", self.id))?
+            .evaluate_interval(bounds);
+        let (satisfied, infeasible) = match self.equality() {
+            Equality::EqualToZero => (
+                range.lower >= -atol && range.upper <= atol,
+                range.lower > atol || range.upper < -atol,
+            ),
+            Equality::LessThanOrEqualToZero => (range.upper <= atol, range.lower > atol),
+            Equality::Unspecified => bail!("Equality of constraint ({}) is not specified", self.id),
+        };
+        Ok(if satisfied {
+            ConstraintClass::TriviallySatisfied
+        } else if infeasible {
+            ConstraintClass::TriviallyInfeasible
+        } else {
+            ConstraintClass::Active
+        })
+    }
+
+    /// Split the linear function `a^T x + c` into `a^T x` and the right-hand side `b = -c`, i.e. `a^T x (= or <=) b`
+    ///
+    /// Terms of the same ID are merged and sorted by ID. This fails if the function is not set or not linear.
+    pub fn as_standard_form(&self) -> Result<(Linear, f64)> {
+        let function = self
+            .function
+            .as_ref()
+            .with_context(|| format!("Function of constraint ({}) is not set", self.id))?;
+        let mut terms = Vec::new();
+        let mut constant = 0.0;
+        for (ids, coefficient) in Polynomial::from(function.clone()).monomials() {
+            match ids.as_slice() {
+                [] => constant += coefficient,
+                [id] => terms.push((*id, coefficient)),
+                _ => bail!("Constraint ({}) is not linear", self.id),
+            }
+        }
+        Ok((Linear::new(terms.into_iter(), 0.0), -constant))
+    }
+}
+
+impl EvaluatedConstraint {
+    /// Create from the value of the constraint function computed outside, e.g. a constraint activity reported by a solver
+    ///
+    /// Other fields like `name` or `dual_variable` are left unset. This fails if `equality` is unspecified
+    /// or `evaluated_value` is not finite, since the feasibility of the constraint cannot be determined.
+    pub fn from_parts(
+        id: u64,
+        equality: Equality,
+        evaluated_value: f64,
+        used_decision_variable_ids: BTreeSet<u64>,
+    ) -> Result<Self> {
+        ensure!(
+            equality != Equality::Unspecified,
+            "Equality of constraint ({id}) is not specified"
+        );
+        ensure!(
+            evaluated_value.is_finite(),
+            "Value of constraint ({id}) is not finite: {evaluated_value}"
+        );
+        Ok(Self {
+            id,
+            equality: equality as i32,
+            evaluated_value,
+            used_decision_variable_ids: used_decision_variable_ids.into_iter().collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Create a [`Constraint`] with the same ID, equality, and metadata, but without the function
+    ///
+    /// The function is not stored in the evaluated constraint. Consult the original [`Instance`](crate::v1::Instance) to restore it.
+    pub fn to_constraint_skeleton(&self) -> Constraint {
+        Constraint {
+            id: self.id,
+            equality: self.equality,
+            function: None,
+            parameters: self.parameters.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+        }
+    }
+
+    /// The minimal absolute tolerance to regard this constraint as satisfied
+    ///
+    /// This is `|f(x)|` for `f(x) = 0`, and `max(f(x), 0)` for `f(x) <= 0`.
+    pub fn required_atol_for_feasibility(&self) -> Result<f64> {
+        match self.equality() {
+            Equality::EqualToZero => Ok(self.evaluated_value.abs()),
+            Equality::LessThanOrEqualToZero => Ok(self.evaluated_value.max(0.0)),
+            Equality::Unspecified => bail!("Unsupported equality: {:?}", self.equality),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::v1::{decision_variable::Kind, Bound, DecisionVariable};
+use anyhow::{bail, Context, Result};
+
+impl Kind {
+    /// Canonical translation into `(is_integer, can_be_zero)` flags for solver adapters
+    ///
+    /// `can_be_zero` means the decision variable can take `0` even if it is out of its bound, i.e. semi-variables.
+    ///
+    /// | Kind             | `is_integer` | `can_be_zero` |
+    /// |:-----------------|:-------------|:--------------|
+    /// | `Binary`         | `true`       | `false`       |
+    /// | `Integer`        | `true`       | `false`       |
+    /// | `Continuous`     | `false`      | `false`       |
+    /// | `SemiInteger`    | `true`       | `true`        |
+    /// | `SemiContinuous` | `false`      | `true`        |
+    ///
+    /// Binary variables are integer variables in `[0, 1]`, and adapters should use [`DecisionVariable::effective_bound`] as their bound.
+    /// This fails for `Unspecified`.
+    pub fn to_variable_flags(&self) -> Result<(bool, bool)> {
+        match self {
+            Kind::Binary | Kind::Integer => Ok((true, false)),
+            Kind::Continuous => Ok((false, false)),
+            Kind::SemiInteger => Ok((true, true)),
+            Kind::SemiContinuous => Ok((false, true)),
+            Kind::Unspecified => bail!("Kind of decision variable is not specified"),
+        }
+    }
+}
+
+impl DecisionVariable {
+    /// Bound containing all values of this decision variable except zero of semi-variables
+    ///
+    /// A missing bound is regarded as unbounded, and the bound of a binary decision variable is intersected with `[0, 1]`.
+    ///
+    /// ```rust
+    /// use ommx::v1::{decision_variable::Kind, Bound, DecisionVariable};
+    ///
+    /// let mut v = DecisionVariable { id: 0, kind: Kind::Binary as i32, ..Default::default() };
+    /// assert_eq!(v.effective_bound(), Bound { lower: 0.0, upper: 1.0 });
+    /// v.bound = Some(Bound { lower: 0.5, upper: 3.0 });
+    /// assert_eq!(v.effective_bound(), Bound { lower: 0.5, upper: 1.0 });
+    /// v.set_kind(Kind::Integer);
+    /// assert_eq!(v.effective_bound(), Bound { lower: 0.5, upper: 3.0 });
+    /// ```
+    pub fn effective_bound(&self) -> Bound {
+        let mut bound = self.bound.clone().unwrap_or_else(Bound::unbounded);
+        if self.kind() == Kind::Binary {
+            bound.lower = bound.lower.max(0.0);
+            bound.upper = bound.upper.min(1.0);
+        }
+        bound
+    }
+
+    /// Key of [`DecisionVariable::parameters`] storing the branching priority for solvers
+    pub const BRANCH_PRIORITY_KEY: &'static str = "org.ommx.branch_priority";
+
+    /// Branching priority stored in parameters, where solvers branch on variables of higher priority first
+    ///
+    /// This fails if the stored value is not an integer.
+    pub fn branch_priority(&self) -> Result<Option<i32>> {
+        self.parameters
+            .get(Self::BRANCH_PRIORITY_KEY)
+            .map(|value| {
+                value.parse().with_context(|| {
+                    format!(
+                        "Branch priority of decision variable ({}) is not an integer: {value}",
+                        self.id
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Store the branching priority in parameters, see [`DecisionVariable::branch_priority`]
+    pub fn set_branch_priority(&mut self, priority: i32) {
+        self.parameters
+            .insert(Self::BRANCH_PRIORITY_KEY.to_string(), priority.to_string());
+    }
+
+    /// Key of [`DecisionVariable::parameters`] storing the value to which solvers should fix the decision variable
+    pub const SOLVER_FIX_KEY: &'static str = "org.ommx.solver.fix";
+
+    /// Key of [`DecisionVariable::parameters`] storing the value which solvers should try first
+    pub const SOLVER_HINT_KEY: &'static str = "org.ommx.solver.hint";
+
+    /// Value to which solvers should fix this decision variable, see [`crate::adapter::solver_bounds`]
+    ///
+    /// Unlike the bound, this does not change the problem itself, and is only a guidance for solvers.
+    /// This fails if the stored value is not a number.
+    pub fn solver_fix(&self) -> Result<Option<f64>> {
+        self.parse_parameter(Self::SOLVER_FIX_KEY, "Fixed value")
+    }
+
+    /// Store the value to which solvers should fix this decision variable, see [`DecisionVariable::solver_fix`]
+    pub fn set_solver_fix(&mut self, value: f64) {
+        self.parameters
+            .insert(Self::SOLVER_FIX_KEY.to_string(), value.to_string());
+    }
+
+    /// Value which solvers should try first for this decision variable, see [`crate::adapter::solver_initial_state`]
+    ///
+    /// This fails if the stored value is not a number.
+    pub fn solver_hint(&self) -> Result<Option<f64>> {
+        self.parse_parameter(Self::SOLVER_HINT_KEY, "Hinted value")
+    }
+
+    /// Store the value which solvers should try first for this decision variable, see [`DecisionVariable::solver_hint`]
+    pub fn set_solver_hint(&mut self, value: f64) {
+        self.parameters
+            .insert(Self::SOLVER_HINT_KEY.to_string(), value.to_string());
+    }
+
+    fn parse_parameter(&self, key: &str, label: &str) -> Result<Option<f64>> {
+        self.parameters
+            .get(key)
+            .map(|value| {
+                value.parse().with_context(|| {
+                    format!(
+                        "{label} of decision variable ({}) is not a number: {value}",
+                        self.id
+                    )
+                })
+            })
+            .transpose()
+    }
+}
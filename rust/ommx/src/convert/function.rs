@@ -0,0 +1,314 @@
+use crate::v1::{function::Function as FunctionEnum, Function, Linear, Polynomial, Quadratic};
+use anyhow::{bail, ensure, Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+impl Function {
+    pub fn used_decision_variable_ids(&self) -> BTreeSet<u64> {
+        match &self.function {
+            Some(FunctionEnum::Linear(linear)) => linear.used_decision_variable_ids(),
+            Some(FunctionEnum::Quadratic(quadratic)) => quadratic.used_decision_variable_ids(),
+            Some(FunctionEnum::Polynomial(poly)) => poly.used_decision_variable_ids(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Degree of the function. This is `O(1)` except for the polynomial form, see [`Polynomial::degree`].
+    ///
+    /// Stored terms are counted even if their coefficients are zero.
+    pub fn degree(&self) -> usize {
+        match &self.function {
+            Some(FunctionEnum::Linear(linear)) => linear.degree(),
+            Some(FunctionEnum::Quadratic(quadratic)) => quadratic.degree(),
+            Some(FunctionEnum::Polynomial(poly)) => poly.degree(),
+            _ => 0,
+        }
+    }
+
+    /// Keep only the terms whose IDs satisfy `keep`, where the constant term has empty IDs
+    pub(crate) fn restrict(&self, keep: impl Fn(&[u64]) -> bool) -> Function {
+        let restrict_linear = |linear: &Linear| Linear {
+            terms: linear
+                .terms
+                .iter()
+                .filter(|term| keep(&[term.id]))
+                .cloned()
+                .collect(),
+            constant: if keep(&[]) { linear.constant } else { 0.0 },
+        };
+        let function = match &self.function {
+            Some(FunctionEnum::Constant(c)) => {
+                FunctionEnum::Constant(if keep(&[]) { *c } else { 0.0 })
+            }
+            Some(FunctionEnum::Linear(linear)) => FunctionEnum::Linear(restrict_linear(linear)),
+            Some(FunctionEnum::Quadratic(quadratic)) => {
+                let mut out = Quadratic {
+                    linear: quadratic.linear.as_ref().map(restrict_linear),
+                    ..Default::default()
+                };
+                for (row, column, value) in itertools::multizip((
+                    quadratic.rows.iter(),
+                    quadratic.columns.iter(),
+                    quadratic.values.iter(),
+                )) {
+                    if keep(&[*row, *column]) {
+                        out.rows.push(*row);
+                        out.columns.push(*column);
+                        out.values.push(*value);
+                    }
+                }
+                FunctionEnum::Quadratic(out)
+            }
+            Some(FunctionEnum::Polynomial(poly)) => FunctionEnum::Polynomial(Polynomial {
+                terms: poly
+                    .terms
+                    .iter()
+                    .filter(|term| keep(&term.ids))
+                    .cloned()
+                    .collect(),
+            }),
+            None => return Function::default(),
+        };
+        function.into()
+    }
+
+    /// Coefficients of linear terms aligned to `ordering`, where `0.0` is used for decision variables not in the function
+    ///
+    /// The constant term is not included, see [`Function::constant_term`].
+    /// This fails if the function has a term of degree two or higher, a decision variable not in `ordering`, or `ordering` has duplicated IDs.
+    pub fn linear_coefficients_dense(&self, ordering: &[u64]) -> Result<Vec<f64>> {
+        let mut position = BTreeMap::new();
+        for (n, id) in ordering.iter().enumerate() {
+            ensure!(
+                position.insert(*id, n).is_none(),
+                "Duplicated ID in ordering: {id}"
+            );
+        }
+        let mut out = vec![0.0; ordering.len()];
+        for (ids, coefficient) in Polynomial::from(self.clone()).monomials() {
+            match ids.as_slice() {
+                [] => {}
+                [id] => {
+                    let n = position
+                        .get(id)
+                        .with_context(|| format!("Decision variable ({id}) is not in ordering"))?;
+                    out[*n] += coefficient;
+                }
+                _ => bail!("Function has a term of degree {}: {ids:?}", ids.len()),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Linear terms `(id, coefficient)` sorted by ID, where terms of the same ID are merged
+    pub fn linear_terms(&self) -> impl Iterator<Item = (u64, f64)> {
+        self.monomials_of_degree(1)
+            .map(|(ids, coefficient)| (ids[0], coefficient))
+    }
+
+    /// Quadratic terms `((id1, id2), coefficient)` with `id1 <= id2` sorted by IDs, where terms of the same pair are merged, e.g. `x1 * x2` and `x2 * x1`
+    pub fn quadratic_terms(&self) -> impl Iterator<Item = ((u64, u64), f64)> {
+        self.monomials_of_degree(2)
+            .map(|(ids, coefficient)| ((ids[0], ids[1]), coefficient))
+    }
+
+    fn monomials_of_degree(&self, degree: usize) -> impl Iterator<Item = (Vec<u64>, f64)> {
+        Polynomial::from(self.clone())
+            .monomials()
+            .into_iter()
+            .filter(move |(ids, _)| ids.len() == degree)
+    }
+
+    /// Constant term of the function. Unset function is regarded as zero.
+    pub fn constant_term(&self) -> f64 {
+        match &self.function {
+            Some(FunctionEnum::Constant(c)) => *c,
+            Some(FunctionEnum::Linear(linear)) => linear.constant,
+            Some(FunctionEnum::Quadratic(quadratic)) => quadratic
+                .linear
+                .as_ref()
+                .map_or(0.0, |linear| linear.constant),
+            Some(FunctionEnum::Polynomial(poly)) => poly.constant_term(),
+            None => 0.0,
+        }
+    }
+
+    /// Replace the constant term by `constant`, keeping the other terms and the form of the function
+    pub fn set_constant_term(&mut self, constant: f64) {
+        match &mut self.function {
+            Some(FunctionEnum::Linear(linear)) => linear.constant = constant,
+            Some(FunctionEnum::Quadratic(quadratic)) => {
+                quadratic
+                    .linear
+                    .get_or_insert_with(Default::default)
+                    .constant = constant
+            }
+            Some(FunctionEnum::Polynomial(poly)) => poly.set_constant_term(constant),
+            Some(FunctionEnum::Constant(_)) | None => {
+                self.function = Some(FunctionEnum::Constant(constant))
+            }
+        }
+    }
+
+    /// Number of stored terms. This is `O(1)`.
+    ///
+    /// Terms of the same IDs are counted separately since they are not merged in the message.
+    pub fn num_terms(&self) -> usize {
+        match &self.function {
+            Some(FunctionEnum::Constant(c)) => usize::from(*c != 0.0),
+            Some(FunctionEnum::Linear(linear)) => linear.num_terms(),
+            Some(FunctionEnum::Quadratic(quadratic)) => quadratic.num_terms(),
+            Some(FunctionEnum::Polynomial(poly)) => poly.num_terms(),
+            None => 0,
+        }
+    }
+
+    /// Substitute the decision variable of `id` by `expr`
+    ///
+    /// The result is represented in the lowest form among linear, quadratic, and polynomial,
+    /// while the function is returned as is if it does not use `id`.
+    pub fn substitute(&self, id: u64, expr: &Function) -> Function {
+        if !self.used_decision_variable_ids().contains(&id) {
+            return self.clone();
+        }
+        let poly = Polynomial::from(self.clone()).substitute(id, &expr.clone().into());
+        Self::from_polynomial_in_lowest_form(poly)
+    }
+
+    /// Represent `poly` in the lowest form among linear, quadratic, and polynomial
+    pub(crate) fn from_polynomial_in_lowest_form(poly: Polynomial) -> Function {
+        if poly.degree() <= 1 {
+            let linear = poly
+                .monomials()
+                .into_iter()
+                .filter(|(ids, _)| ids.len() == 1)
+                .map(|(ids, coefficient)| (ids[0], coefficient));
+            return Linear::new(linear, poly.constant_term()).into();
+        }
+        Quadratic::try_from(poly.clone())
+            .map(Function::from)
+            .unwrap_or_else(|_| poly.into())
+    }
+
+    /// Replace powers of binary variables of `binary_ids` by themselves, keeping the form of the function
+    pub fn simplify_binary_squares(&mut self, binary_ids: &BTreeSet<u64>) {
+        match &mut self.function {
+            Some(FunctionEnum::Quadratic(quadratic)) => {
+                quadratic.simplify_binary_squares(binary_ids)
+            }
+            Some(FunctionEnum::Polynomial(poly)) => poly.simplify_binary_squares(binary_ids),
+            _ => {}
+        }
+    }
+}
+
+impl From<FunctionEnum> for Function {
+    fn from(f: FunctionEnum) -> Self {
+        Self { function: Some(f) }
+    }
+}
+
+impl From<Linear> for Function {
+    fn from(linear: Linear) -> Self {
+        Self {
+            function: Some(FunctionEnum::Linear(linear)),
+        }
+    }
+}
+
+impl From<Quadratic> for Function {
+    fn from(q: Quadratic) -> Self {
+        Self {
+            function: Some(FunctionEnum::Quadratic(q)),
+        }
+    }
+}
+
+impl From<f64> for Function {
+    fn from(constant: f64) -> Self {
+        Self {
+            function: Some(FunctionEnum::Constant(constant)),
+        }
+    }
+}
+
+impl From<Polynomial> for Function {
+    fn from(poly: Polynomial) -> Self {
+        Self {
+            function: Some(FunctionEnum::Polynomial(poly)),
+        }
+    }
+}
+
+/// Unset function is regarded as zero
+impl From<Function> for Polynomial {
+    fn from(f: Function) -> Self {
+        match f.function {
+            Some(FunctionEnum::Constant(c)) => c.into(),
+            Some(FunctionEnum::Linear(linear)) => linear.into(),
+            Some(FunctionEnum::Quadratic(quadratic)) => quadratic.into(),
+            Some(FunctionEnum::Polynomial(poly)) => poly,
+            None => Polynomial::default(),
+        }
+    }
+}
+
+impl Add for Function {
+    type Output = Self;
+
+    /// The result is represented in the lowest form which can represent both sides, e.g. linear + quadratic is quadratic.
+    /// Unset function is regarded as zero.
+    fn add(self, rhs: Self) -> Self {
+        let lhs = self.function.unwrap_or(FunctionEnum::Constant(0.0));
+        let rhs = rhs.function.unwrap_or(FunctionEnum::Constant(0.0));
+        match (lhs, rhs) {
+            (FunctionEnum::Polynomial(p), other) | (other, FunctionEnum::Polynomial(p)) => {
+                (p + Polynomial::from(Function::from(other))).into()
+            }
+            (FunctionEnum::Constant(a), FunctionEnum::Constant(b)) => (a + b).into(),
+            (FunctionEnum::Constant(c), FunctionEnum::Linear(mut l))
+            | (FunctionEnum::Linear(mut l), FunctionEnum::Constant(c)) => {
+                l.constant += c;
+                l.into()
+            }
+            (FunctionEnum::Constant(c), FunctionEnum::Quadratic(q))
+            | (FunctionEnum::Quadratic(q), FunctionEnum::Constant(c)) => {
+                (q + Linear::new(std::iter::empty(), c).into()).into()
+            }
+            (FunctionEnum::Linear(a), FunctionEnum::Linear(b)) => (a + b).into(),
+            (FunctionEnum::Linear(l), FunctionEnum::Quadratic(q))
+            | (FunctionEnum::Quadratic(q), FunctionEnum::Linear(l)) => (q + l.into()).into(),
+            (FunctionEnum::Quadratic(a), FunctionEnum::Quadratic(b)) => (a + b).into(),
+        }
+    }
+}
+
+impl Neg for Function {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+impl Sub for Function {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul<f64> for Function {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self {
+        let function = match self.function {
+            Some(FunctionEnum::Constant(c)) => FunctionEnum::Constant(c * rhs),
+            Some(FunctionEnum::Linear(linear)) => FunctionEnum::Linear(linear * rhs),
+            Some(FunctionEnum::Quadratic(q)) => FunctionEnum::Quadratic(q * rhs),
+            Some(FunctionEnum::Polynomial(poly)) => FunctionEnum::Polynomial(poly * rhs),
+            None => FunctionEnum::Constant(0.0),
+        };
+        function.into()
+    }
+}
@@ -0,0 +1,1792 @@
+use crate::v1::{
+    decision_variable::Kind, instance::Sense, Bound, Constraint, DecisionVariable, Equality,
+    Function, Instance, Linear, Polynomial, Quadratic, State,
+};
+use anyhow::{bail, ensure, Context, Result};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Role of auxiliary decision variables introduced by transformations of [`Instance`]
+///
+/// Auxiliary decision variables are identified by their names `ommx.*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum AuxiliaryRole {
+    /// `ommx.epigraph`, introduced by [`Instance::epigraph_reformulation`]
+    Epigraph,
+    /// `ommx.reduce_to_quadratic`, introduced by [`Instance::reduce_to_quadratic`]
+    ReduceToQuadratic,
+    /// `ommx.soften_constraint`, introduced by [`Instance::soften_constraint`]
+    SoftenConstraint,
+    /// `ommx.log_encode`, introduced by [`Instance::log_encode`]
+    LogEncode,
+    /// `ommx.unary_encode`, introduced by [`Instance::unary_encode`]
+    UnaryEncode,
+    /// `ommx.domain_encode`, introduced by [`Instance::domain_encode`]
+    DomainEncode,
+}
+
+impl AuxiliaryRole {
+    /// Name of decision variables and constraints of this role
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuxiliaryRole::Epigraph => "ommx.epigraph",
+            AuxiliaryRole::ReduceToQuadratic => "ommx.reduce_to_quadratic",
+            AuxiliaryRole::SoftenConstraint => "ommx.soften_constraint",
+            AuxiliaryRole::LogEncode => "ommx.log_encode",
+            AuxiliaryRole::UnaryEncode => "ommx.unary_encode",
+            AuxiliaryRole::DomainEncode => "ommx.domain_encode",
+        }
+    }
+}
+
+/// Encoding of an integer decision variable by binary ones, used in [`Instance::encode_integer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntegerEncoding {
+    /// [`Instance::log_encode`]
+    Log,
+    /// [`Instance::unary_encode`]
+    Unary,
+    /// [`Instance::domain_encode`]
+    Domain,
+}
+
+impl Instance {
+    /// Create an unconstrained instance from a QUBO `sum_{(i, j)} Q_{ij} x_i x_j + constant`
+    ///
+    /// Binary decision variables are created for all IDs appearing in `quad`.
+    /// This is the inverse of [`Instance::as_qubo_format`] for a minimization problem:
+    ///
+    /// ```rust
+    /// use ommx::v1::{instance::Sense, Instance};
+    /// use maplit::btreemap;
+    ///
+    /// let quad = btreemap! { (0, 0) => 1.0, (0, 2) => -2.0, (2, 2) => 0.5 };
+    /// let instance = Instance::from_qubo(&quad, 3.0, Sense::Minimize).unwrap();
+    /// assert_eq!(instance.decision_variables.len(), 2);
+    /// assert_eq!(instance.as_qubo_format().unwrap(), (quad, 3.0));
+    /// ```
+    pub fn from_qubo(
+        quad: &BTreeMap<(u64, u64), f64>,
+        constant: f64,
+        sense: Sense,
+    ) -> Result<Instance> {
+        ensure!(sense != Sense::Unspecified, "Sense is not specified");
+        let mut ids = BTreeSet::new();
+        for (&(i, j), value) in quad {
+            ensure!(
+                value.is_finite(),
+                "Coefficient of ({i}, {j}) is not finite: {value}"
+            );
+            ids.insert(i);
+            ids.insert(j);
+        }
+        let objective = Quadratic::from_terms(
+            quad.clone(),
+            Some(Linear::new(std::iter::empty(), constant)),
+        );
+        Ok(Instance {
+            decision_variables: ids
+                .into_iter()
+                .map(|id| DecisionVariable {
+                    id,
+                    kind: Kind::Binary as i32,
+                    ..Default::default()
+                })
+                .collect(),
+            objective: Some(objective.into()),
+            sense: sense as i32,
+            ..Default::default()
+        })
+    }
+
+    /// IDs of decision variables used in the objective and constraints
+    pub fn used_decision_variable_ids(&self) -> BTreeSet<u64> {
+        let mut used = self
+            .objective
+            .as_ref()
+            .map(|f| f.used_decision_variable_ids())
+            .unwrap_or_default();
+        for c in &self.constraints {
+            if let Some(f) = &c.function {
+                used.extend(f.used_decision_variable_ids());
+            }
+        }
+        used
+    }
+
+    /// Constant term of the objective. Unset objective is regarded as zero.
+    pub fn objective_constant(&self) -> f64 {
+        self.objective.as_ref().map_or(0.0, |f| f.constant_term())
+    }
+
+    /// Replace the constant term of the objective by `constant`, keeping the other terms
+    pub fn set_objective_constant(&mut self, constant: f64) {
+        self.objective
+            .get_or_insert_with(Default::default)
+            .set_constant_term(constant);
+    }
+
+    /// Substitute the decision variable of `id` by `expr` only in the objective, see [`Function::substitute`]
+    ///
+    /// Constraints are kept as is, and thus the decision variable of `id` is kept in the instance.
+    /// Fails if `id` or a decision variable used in `expr` is not defined.
+    pub fn substitute_in_objective(&mut self, id: u64, expr: Function) -> Result<()> {
+        let defined: BTreeSet<u64> = self.decision_variables.iter().map(|v| v.id).collect();
+        ensure!(
+            defined.contains(&id),
+            "Decision variable ({id}) is not defined"
+        );
+        for used in expr.used_decision_variable_ids() {
+            ensure!(
+                defined.contains(&used),
+                "Decision variable ({used}) used in the expression is not defined"
+            );
+        }
+        self.mutate_checked(|instance| {
+            if let Some(objective) = instance.objective.as_mut() {
+                *objective = objective.substitute(id, &expr);
+            }
+            Ok(())
+        })
+    }
+
+    /// Fix decision variables to the given values, substituting them into the objective and constraints and removing them from the instance
+    ///
+    /// Each value must be in [`DecisionVariable::effective_bound`], an integer for binary and integer variables,
+    /// and may also be zero for semi-continuous and semi-integer variables.
+    /// Since the instance does not record the removed decision variables, keep `values` to restore the state for the original instance.
+    /// Constraints are kept even if they become constant.
+    ///
+    /// ```rust
+    /// use ommx::v1::{decision_variable::Kind, instance::Sense, DecisionVariable, Instance, Linear};
+    /// use maplit::{btreeset, hashmap};
+    ///
+    /// // Minimize `x0 + 2 x1 + 3 x2`
+    /// let mut instance = Instance {
+    ///     decision_variables: (0..3)
+    ///         .map(|id| DecisionVariable { id, kind: Kind::Binary as i32, ..Default::default() })
+    ///         .collect(),
+    ///     objective: Some(Linear::new([(0, 1.0), (1, 2.0), (2, 3.0)].into_iter(), 0.0).into()),
+    ///     sense: Sense::Minimize as i32,
+    ///     ..Default::default()
+    /// };
+    /// instance.fix_variables(&hashmap! { 1 => 1.0, 2 => 0.0 }).unwrap();
+    /// assert_eq!(instance.objective, Some(Linear::new([(0, 1.0)].into_iter(), 2.0).into()));
+    /// assert_eq!(instance.used_decision_variable_ids(), btreeset! { 0 });
+    /// assert_eq!(instance.decision_variables.len(), 1);
+    ///
+    /// // Binary variables cannot be fixed to fractional values
+    /// assert!(instance.fix_variables(&hashmap! { 0 => 0.5 }).is_err());
+    /// ```
+    pub fn fix_variables(&mut self, values: &HashMap<u64, f64>) -> Result<()> {
+        for (&id, &value) in values {
+            let v = self
+                .decision_variables
+                .iter()
+                .find(|v| v.id == id)
+                .with_context(|| format!("Decision variable ({id}) is not defined"))?;
+            let kind = v.kind();
+            let (is_integer, can_be_zero) = kind.to_variable_flags()?;
+            let Bound { lower, upper } = v.effective_bound();
+            ensure!(
+                (lower <= value && value <= upper) || (can_be_zero && value == 0.0),
+                "Value {value} of decision variable ({id}) is out of the bound [{lower}, {upper}]"
+            );
+            ensure!(
+                !is_integer || value.fract() == 0.0,
+                "Value {value} of decision variable ({id}) is not an integer while it is {kind:?}"
+            );
+        }
+        self.mutate_checked(|instance| {
+            let functions = instance.objective.iter_mut().chain(
+                instance
+                    .constraints
+                    .iter_mut()
+                    .filter_map(|c| c.function.as_mut()),
+            );
+            for f in functions {
+                for (&id, &value) in values {
+                    *f = f.substitute(id, &Function::from(value));
+                }
+            }
+            instance
+                .decision_variables
+                .retain(|v| !values.contains_key(&v.id));
+            Ok(())
+        })
+    }
+
+    /// IDs of auxiliary decision variables of `role`
+    pub fn auxiliary_variables(&self, role: AuxiliaryRole) -> Vec<u64> {
+        self.decision_variables
+            .iter()
+            .filter(|v| v.name.as_deref() == Some(role.name()))
+            .map(|v| v.id)
+            .collect()
+    }
+
+    /// Check if the instance is a convex quadratic programming (QP) problem
+    ///
+    /// All decision variables must be continuous, all constraints must be linear, and the objective must be
+    /// a quadratic function whose Hessian is positive semidefinite for minimization, or negative semidefinite for maximization.
+    /// The semidefiniteness is tested by the symmetric-pivoted Cholesky decomposition of the Hessian,
+    /// where pivots larger than `-atol` are regarded as non-negative, and pivots within `atol` as zero.
+    /// This is cheaper than computing eigenvalues, and `atol` plays the role of the tolerance for the smallest eigenvalue.
+    pub fn is_convex_qp(&self, atol: f64) -> bool {
+        let sign = match self.sense() {
+            Sense::Minimize => 1.0,
+            Sense::Maximize => -1.0,
+            Sense::Unspecified => return false,
+        };
+        if self
+            .decision_variables
+            .iter()
+            .any(|v| v.kind() != Kind::Continuous)
+        {
+            return false;
+        }
+        if self
+            .constraints
+            .iter()
+            .any(|c| c.function.as_ref().map_or(0, |f| f.degree()) > 1)
+        {
+            return false;
+        }
+        let Some(objective) = &self.objective else {
+            return false;
+        };
+
+        let mut quad = BTreeMap::new();
+        for (ids, coefficient) in Polynomial::from(objective.clone()).monomials() {
+            match ids.as_slice() {
+                [] | [_] => {}
+                [i, j] => *quad.entry((*i, *j)).or_insert(0.0) += sign * coefficient,
+                _ => return false,
+            }
+        }
+        is_hessian_positive_semidefinite(&quad, atol)
+    }
+
+    /// IDs of decision variables whose coefficients are within `atol` of zero in the objective and all constraints
+    ///
+    /// Coefficients of the same monomial are merged before comparison. Decision variables not used at all are also returned.
+    pub fn zero_impact_variables(&self, atol: f64) -> Vec<u64> {
+        let mut max_coefficients: BTreeMap<u64, f64> = BTreeMap::new();
+        let functions = self
+            .objective
+            .iter()
+            .chain(self.constraints.iter().filter_map(|c| c.function.as_ref()));
+        for f in functions {
+            for (ids, coefficient) in Polynomial::from(f.clone()).monomials() {
+                for id in ids {
+                    let max = max_coefficients.entry(id).or_insert(0.0);
+                    *max = max.max(coefficient.abs());
+                }
+            }
+        }
+        self.decision_variables
+            .iter()
+            .filter(|v| max_coefficients.get(&v.id).unwrap_or(&0.0) <= &atol)
+            .map(|v| v.id)
+            .collect()
+    }
+
+    /// Intervals of decision variables which contain all values they can take
+    ///
+    /// This is [`DecisionVariable::effective_bound`] extended to contain zero for semi-variables.
+    pub fn variable_intervals(&self) -> BTreeMap<u64, Bound> {
+        self.decision_variables
+            .iter()
+            .map(|v| {
+                let mut bound = v.effective_bound();
+                if matches!(v.kind(), Kind::SemiInteger | Kind::SemiContinuous) {
+                    bound.lower = bound.lower.min(0.0);
+                    bound.upper = bound.upper.max(0.0);
+                }
+                (v.id, bound)
+            })
+            .collect()
+    }
+
+    /// Project values of `state` into the bounds of decision variables, leaving constraints untouched
+    ///
+    /// Values are clamped into `[lower, upper]`, and values of binary, integer, and semi-integer variables are then rounded
+    /// to the nearest integer in the bound. Values of semi-variables become zero if they are within `atol` of zero
+    /// or nearer to zero than to the projected value, e.g. `0.4` becomes `0` for a bound `[1, 5]`.
+    /// Values of IDs not defined as decision variables are kept as they are.
+    pub fn clamp_state_to_bounds(&self, state: &State, atol: f64) -> State {
+        let mut out = state.clone();
+        for v in &self.decision_variables {
+            let Some(value) = out.entries.get_mut(&v.id) else {
+                continue;
+            };
+            let kind = v.kind();
+            let Bound { lower, upper } = v.effective_bound();
+            let mut projected = *value;
+            if lower <= upper {
+                projected = projected.clamp(lower, upper);
+            }
+            if matches!(kind, Kind::Binary | Kind::Integer | Kind::SemiInteger) {
+                let (lower, upper) = (lower.ceil(), upper.floor());
+                projected = projected.round();
+                if lower <= upper {
+                    projected = projected.clamp(lower, upper);
+                }
+            }
+            if matches!(kind, Kind::SemiInteger | Kind::SemiContinuous)
+                && (value.abs() <= atol || value.abs() <= (*value - projected).abs())
+            {
+                projected = 0.0;
+            }
+            *value = projected;
+        }
+        out
+    }
+
+    /// Optimistic bound of the objective from the bounds of decision variables, ignoring constraints
+    ///
+    /// This is a lower bound of the optimal value for minimization, and an upper bound for maximization,
+    /// computed by [`Function::evaluate_interval`] on [`Instance::variable_intervals`].
+    /// Returns `None` if the bound is infinite, or the sense or the objective is not set.
+    pub fn trivial_bound(&self) -> Option<f64> {
+        let range = self
+            .objective
+            .as_ref()?
+            .evaluate_interval(&self.variable_intervals());
+        let bound = match self.sense() {
+            Sense::Minimize => range.lower,
+            Sense::Maximize => range.upper,
+            Sense::Unspecified => return None,
+        };
+        bound.is_finite().then_some(bound)
+    }
+
+    /// Check if two instances represent the same problem up to a constant difference of the objectives
+    ///
+    /// Sense, kinds and bounds of decision variables, and equalities of constraints must be the same,
+    /// and coefficients of the objectives except the constant terms and of constraint functions must be the same within `atol`.
+    /// Decision variables and constraints are matched by their IDs, and metadata like names are not compared.
+    pub fn equivalent_up_to_objective_shift(&self, other: &Instance, atol: f64) -> bool {
+        fn is_close(
+            lhs: &Option<Function>,
+            rhs: &Option<Function>,
+            atol: f64,
+            shift: bool,
+        ) -> bool {
+            let diff =
+                Polynomial::from(lhs.clone().unwrap_or_default() - rhs.clone().unwrap_or_default());
+            diff.monomials()
+                .into_iter()
+                .all(|(ids, coefficient)| (shift && ids.is_empty()) || coefficient.abs() <= atol)
+        }
+
+        if self.sense() != other.sense() || !is_close(&self.objective, &other.objective, atol, true)
+        {
+            return false;
+        }
+
+        let variables = |instance: &Instance| -> BTreeMap<u64, _> {
+            instance
+                .decision_variables
+                .iter()
+                .map(|v| (v.id, (v.kind(), v.bound.clone())))
+                .collect()
+        };
+        if self.decision_variables.len() != other.decision_variables.len()
+            || variables(self) != variables(other)
+        {
+            return false;
+        }
+
+        if self.constraints.len() != other.constraints.len() {
+            return false;
+        }
+        let others: BTreeMap<u64, &Constraint> =
+            other.constraints.iter().map(|c| (c.id, c)).collect();
+        let mut ids = BTreeSet::new();
+        self.constraints.iter().all(|c| {
+            ids.insert(c.id)
+                && others.get(&c.id).is_some_and(|o| {
+                    c.equality() == o.equality() && is_close(&c.function, &o.function, atol, false)
+                })
+        })
+    }
+
+    /// Convert integer decision variables which can take only `0` and `1` into binary ones, and return their IDs
+    ///
+    /// The bound of an integer decision variable is rounded inward allowing `atol`, i.e. `[ceil(lower - atol), floor(upper + atol)]`,
+    /// and the variable is converted if it is `[0, 1]`. The bound of converted variables is replaced by exactly `[0, 1]`.
+    ///
+    /// ```rust
+    /// use ommx::v1::{decision_variable::Kind, Bound, DecisionVariable, Instance};
+    ///
+    /// let integer = |id, lower, upper| DecisionVariable {
+    ///     id,
+    ///     kind: Kind::Integer as i32,
+    ///     bound: Some(Bound { lower, upper }),
+    ///     ..Default::default()
+    /// };
+    /// let mut instance = Instance {
+    ///     decision_variables: vec![integer(0, 0.0, 1.0), integer(1, -0.5, 1.9), integer(2, 0.0, 2.0)],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(instance.tighten_integer_to_binary(1e-9), vec![0, 1]);
+    /// assert_eq!(instance.decision_variables[1].kind(), Kind::Binary);
+    /// assert_eq!(instance.decision_variables[1].bound, Some(Bound { lower: 0.0, upper: 1.0 }));
+    /// assert_eq!(instance.decision_variables[2].kind(), Kind::Integer);
+    /// ```
+    pub fn tighten_integer_to_binary(&mut self, atol: f64) -> Vec<u64> {
+        self.mutate_checked(|instance| {
+            let mut converted = Vec::new();
+            for v in &mut instance.decision_variables {
+                if v.kind() != Kind::Integer {
+                    continue;
+                }
+                let Some(bound) = &v.bound else {
+                    continue;
+                };
+                if (bound.lower - atol).ceil() == 0.0 && (bound.upper + atol).floor() == 1.0 {
+                    v.set_kind(Kind::Binary);
+                    v.bound = Some(Bound {
+                        lower: 0.0,
+                        upper: 1.0,
+                    });
+                    converted.push(v.id);
+                }
+            }
+            converted
+        })
+    }
+
+    /// Replace `x^2` (and higher powers) by `x` for binary decision variables in the objective and constraints
+    ///
+    /// This does not change the value of the functions on binary states,
+    /// and is useful to lower the degree of functions before converting to QUBO.
+    pub fn simplify_binary_squares(&mut self) {
+        self.mutate_checked(|instance| {
+            let binary_ids: BTreeSet<u64> = instance
+                .decision_variables
+                .iter()
+                .filter(|v| v.kind() == Kind::Binary)
+                .map(|v| v.id)
+                .collect();
+            if let Some(objective) = instance.objective.as_mut() {
+                objective.simplify_binary_squares(&binary_ids);
+            }
+            for c in &mut instance.constraints {
+                if let Some(f) = c.function.as_mut() {
+                    f.simplify_binary_squares(&binary_ids);
+                }
+            }
+        })
+    }
+
+    /// Check the invariants of the instance
+    ///
+    /// - Sense and objective are set.
+    /// - IDs of decision variables are unique, and their kinds are specified.
+    /// - Bounds are not empty, and contain an integer for binary and integer variables.
+    /// - Decision variables used in the objective and constraints are defined.
+    /// - IDs of constraints are unique, and their equalities and functions are set.
+    ///
+    /// An instance without decision variables and constraints is valid as long as its objective is set:
+    ///
+    /// ```rust
+    /// use ommx::{Evaluate, v1::{instance::Sense, Function, Instance, State}};
+    ///
+    /// let instance = Instance {
+    ///     objective: Some(Function::from(3.0)),
+    ///     sense: Sense::Minimize as i32,
+    ///     ..Default::default()
+    /// };
+    /// instance.verify_invariants().unwrap();
+    /// assert_eq!(instance.reserve_ids(2), vec![0, 1]);
+    ///
+    /// let (solution, used_ids) = instance.evaluate(&State::default()).unwrap();
+    /// assert_eq!(solution.objective, 3.0);
+    /// assert!(solution.feasible);
+    /// assert!(used_ids.is_empty());
+    /// ```
+    pub fn verify_invariants(&self) -> Result<()> {
+        ensure!(self.sense() != Sense::Unspecified, "Sense is not specified");
+        ensure!(self.objective.is_some(), "Objective is not set");
+
+        let mut defined = BTreeSet::new();
+        for v in &self.decision_variables {
+            ensure!(
+                defined.insert(v.id),
+                "Duplicated decision variable ID: {}",
+                v.id
+            );
+            let kind = v.kind();
+            ensure!(
+                kind != Kind::Unspecified,
+                "Kind of decision variable ({}) is not specified",
+                v.id
+            );
+            if let Some(bound) = &v.bound {
+                ensure!(
+                    bound.lower <= bound.upper,
+                    "Bound of decision variable ({}) is empty: [{}, {}]",
+                    v.id,
+                    bound.lower,
+                    bound.upper
+                );
+                if !matches!(kind, Kind::Binary | Kind::Integer) {
+                    continue;
+                }
+                let Bound { lower, upper } = v.effective_bound();
+                ensure!(
+                    lower.ceil() <= upper.floor(),
+                    "Bound of {:?} decision variable ({}) contains no integer: [{}, {}]",
+                    kind,
+                    v.id,
+                    bound.lower,
+                    bound.upper
+                );
+            }
+        }
+        for id in self.used_decision_variable_ids() {
+            ensure!(
+                defined.contains(&id),
+                "Decision variable ({id}) is used but not defined"
+            );
+        }
+
+        let mut constraint_ids = BTreeSet::new();
+        for c in &self.constraints {
+            ensure!(
+                constraint_ids.insert(c.id),
+                "Duplicated constraint ID: {}",
+                c.id
+            );
+            ensure!(
+                c.equality() != Equality::Unspecified,
+                "Equality of constraint ({}) is not specified",
+                c.id
+            );
+            ensure!(
+                c.function.is_some(),
+                "Function of constraint ({}) is not set",
+                c.id
+            );
+        }
+        Ok(())
+    }
+
+    /// Apply the mutation `f`, and check in debug build that it keeps the invariants if they held before, see [`Instance::verify_invariants`]
+    ///
+    /// The check runs whatever `f` returns, so a mutation failing halfway must not break the invariants either.
+    fn mutate_checked<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let held = cfg!(debug_assertions) && self.verify_invariants().is_ok();
+        let out = f(self);
+        if held {
+            if let Err(e) = self.verify_invariants() {
+                panic!("Instance invariants are broken: {e:#}");
+            }
+        }
+        out
+    }
+
+    /// Reserve `n` consecutive IDs for new decision variables
+    ///
+    /// The IDs are larger than any ID of decision variables defined or used in this instance.
+    /// Methods introducing auxiliary decision variables, e.g. [`Instance::epigraph_reformulation`], allocate IDs in the same way.
+    /// The reservation is not recorded in the instance, so add decision variables of these IDs before calling this or such methods again.
+    pub fn reserve_ids(&self, n: usize) -> Vec<u64> {
+        let start = self.next_variable_id();
+        (start..start + n as u64).collect()
+    }
+
+    /// An ID which is not used for any decision variable defined or used in this instance
+    fn next_variable_id(&self) -> u64 {
+        self.decision_variables
+            .iter()
+            .map(|v| v.id)
+            .chain(self.used_decision_variable_ids())
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    /// An ID which is not used for any constraint in this instance
+    fn next_constraint_id(&self) -> u64 {
+        self.constraints
+            .iter()
+            .map(|c| c.id)
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    /// Create an unconstrained instance from a HUBO `sum_{S} c_S prod_{i in S} x_i + constant`
+    ///
+    /// Each term is keyed by the set of IDs since `x^k = x` for binary variables.
+    /// Binary decision variables are created for all IDs appearing in `terms`.
+    pub fn from_hubo(
+        terms: &BTreeMap<BTreeSet<u64>, f64>,
+        constant: f64,
+        sense: Sense,
+    ) -> Result<Instance> {
+        ensure!(sense != Sense::Unspecified, "Sense is not specified");
+        let mut ids = BTreeSet::new();
+        let mut monomials = BTreeMap::new();
+        for (term, value) in terms {
+            ensure!(
+                value.is_finite(),
+                "Coefficient of {term:?} is not finite: {value}"
+            );
+            ids.extend(term.iter().cloned());
+            *monomials
+                .entry(term.iter().cloned().collect::<Vec<_>>())
+                .or_insert(0.0) += value;
+        }
+        *monomials.entry(Vec::new()).or_insert(0.0) += constant;
+        Ok(Instance {
+            decision_variables: ids
+                .into_iter()
+                .map(|id| DecisionVariable {
+                    id,
+                    kind: Kind::Binary as i32,
+                    ..Default::default()
+                })
+                .collect(),
+            objective: Some(Polynomial::from_terms(monomials).into()),
+            sense: sense as i32,
+            ..Default::default()
+        })
+    }
+
+    /// Reformulate into the epigraph form, and return the ID of the introduced decision variable `t`.
+    ///
+    /// For a minimization problem `min f(x)`, this adds a continuous decision variable `t` named `ommx.epigraph`
+    /// and a constraint `f(x) - t <= 0`, and replaces the objective with `t`.
+    /// For a maximization problem, the constraint is `t - f(x) <= 0` and `t` is maximized.
+    pub fn epigraph_reformulation(&mut self) -> Result<u64> {
+        let sense = self.sense();
+        if sense == Sense::Unspecified {
+            bail!("Sense is not specified");
+        }
+        let t = self.next_variable_id();
+        self.mutate_checked(|instance| {
+            let objective = instance.objective.take().context("Objective is not set")?;
+            let t_function: Function = Linear::single_term(t, 1.0).into();
+            let function = if sense == Sense::Maximize {
+                t_function.clone() - objective
+            } else {
+                objective - t_function.clone()
+            };
+
+            instance.decision_variables.push(DecisionVariable {
+                id: t,
+                kind: Kind::Continuous as i32,
+                name: Some(AuxiliaryRole::Epigraph.name().to_string()),
+                ..Default::default()
+            });
+            instance.constraints.push(Constraint {
+                id: instance.next_constraint_id(),
+                equality: Equality::LessThanOrEqualToZero as i32,
+                function: Some(function),
+                name: Some(AuxiliaryRole::Epigraph.name().to_string()),
+                ..Default::default()
+            });
+            instance.objective = Some(t_function);
+            Ok(t)
+        })
+    }
+
+    /// Add a constraint bounding the current objective by `value` with the tolerance `atol`, and return its ID
+    ///
+    /// The constraint is `f(x) - (value + atol) <= 0` for a minimization problem, and `(value - atol) - f(x) <= 0` for a maximization problem.
+    /// This is intended for lexicographic optimization, where the optimal value of an objective is kept
+    /// while the next objective is optimized after replacing [`Instance::objective`].
+    pub fn add_objective_bound_constraint(&mut self, value: f64, atol: f64) -> Result<u64> {
+        ensure!(
+            value.is_finite(),
+            "Bound of the objective is not finite: {value}"
+        );
+        ensure!(atol >= 0.0, "Tolerance must be non-negative: {atol}");
+        self.mutate_checked(|instance| {
+            let objective = instance.objective.clone().context("Objective is not set")?;
+            let function = match instance.sense() {
+                Sense::Minimize => objective - Function::from(value + atol),
+                Sense::Maximize => Function::from(value - atol) - objective,
+                Sense::Unspecified => bail!("Sense is not specified"),
+            };
+            let id = instance.next_constraint_id();
+            instance.constraints.push(Constraint {
+                id,
+                equality: Equality::LessThanOrEqualToZero as i32,
+                function: Some(function),
+                ..Default::default()
+            });
+            Ok(id)
+        })
+    }
+
+    /// Convert the constraint of `id` into a soft one penalized by `weight` times its violation, and returns the ID of the deviation variable
+    ///
+    /// A non-negative continuous deviation variable `s` named `ommx.soften_constraint` with subscripts `[id]` is introduced,
+    /// and `f(x) <= 0` is replaced by `f(x) - s <= 0`. `f(x) = 0` is replaced by `f(x) - s <= 0`, and `-f(x) - s <= 0`
+    /// is added as a new constraint named `ommx.soften_constraint`, i.e. `|f(x)| <= s`.
+    /// Then `weight * s` is added to the objective (subtracted for maximization).
+    pub fn soften_constraint(&mut self, id: u64, weight: f64) -> Result<u64> {
+        ensure!(weight > 0.0, "Weight must be positive: {weight}");
+        let sign = match self.sense() {
+            Sense::Minimize => 1.0,
+            Sense::Maximize => -1.0,
+            Sense::Unspecified => bail!("Sense is not specified"),
+        };
+        self.mutate_checked(|instance| {
+            let s = instance.next_variable_id();
+            let next_constraint_id = instance.next_constraint_id();
+            let constraint = instance
+                .constraints
+                .iter_mut()
+                .find(|c| c.id == id)
+                .with_context(|| format!("Constraint ({id}) is not found"))?;
+            let equality = constraint.equality();
+            ensure!(
+                equality != Equality::Unspecified,
+                "Equality of constraint ({id}) is not specified"
+            );
+            let f = constraint
+                .function
+                .take()
+                .with_context(|| format!("Function of constraint ({id}) is not set"))?;
+            let s_function: Function = Linear::single_term(s, 1.0).into();
+            constraint.function = Some(f.clone() - s_function.clone());
+            constraint.set_equality(Equality::LessThanOrEqualToZero);
+            if equality == Equality::EqualToZero {
+                instance.constraints.push(Constraint {
+                    id: next_constraint_id,
+                    equality: Equality::LessThanOrEqualToZero as i32,
+                    function: Some(-f - s_function.clone()),
+                    name: Some(AuxiliaryRole::SoftenConstraint.name().to_string()),
+                    ..Default::default()
+                });
+            }
+
+            instance.decision_variables.push(DecisionVariable {
+                id: s,
+                kind: Kind::Continuous as i32,
+                bound: Some(Bound {
+                    lower: 0.0,
+                    upper: f64::INFINITY,
+                }),
+                name: Some(AuxiliaryRole::SoftenConstraint.name().to_string()),
+                subscripts: vec![id as i64],
+                ..Default::default()
+            });
+            let objective = instance.objective.take().unwrap_or_default();
+            instance.objective = Some(objective + s_function * (sign * weight));
+            Ok(s)
+        })
+    }
+
+    /// Encode the integer decision variable `x` of `id` by binary ones, and returns the expression `x = l + sum_i c_i b_i`
+    ///
+    /// For the bound `[l, u]` of `x` rounded to integers, `m = ceil(log2(u - l + 1))` binary decision variables `b_i`
+    /// named `ommx.log_encode` with subscripts `[id, i]` are introduced,
+    /// where `c_i = 2^i` for `i < m - 1` and `c_{m-1} = u - l - (2^{m-1} - 1)` so that the expression ranges exactly over `[l, u]`.
+    /// No decision variable is introduced if `l = u`.
+    ///
+    /// This does not substitute `x` by the returned expression, which is the responsibility of the caller.
+    /// Use [`Instance::log_encode_and_substitute`] unless `x` has to be kept in the objective or constraints.
+    pub fn log_encode(&mut self, id: u64) -> Result<Linear> {
+        self.mutate_checked(|instance| {
+            let (lower, upper) = instance.integer_bound_for_encoding(id)?;
+            let width = upper - lower;
+            let m = (width + 1.0).log2().ceil() as usize;
+            let start = instance.next_variable_id();
+            let mut terms = Vec::with_capacity(m);
+            for i in 0..m {
+                let coefficient = if i + 1 < m {
+                    2.0_f64.powi(i as i32)
+                } else {
+                    width - (2.0_f64.powi(i as i32) - 1.0)
+                };
+                let b = start + i as u64;
+                instance.push_encoding_binary(b, AuxiliaryRole::LogEncode, id, i);
+                terms.push((b, coefficient));
+            }
+            Ok(Linear::new(terms.into_iter(), lower))
+        })
+    }
+
+    /// Encode the integer decision variable `x` of `id` by binary ones in the unary way, and returns the expression `x = l + sum_i b_i`
+    ///
+    /// For the bound `[l, u]` of `x` rounded to integers, `u - l` binary decision variables `b_i`
+    /// named `ommx.unary_encode` with subscripts `[id, i]` are introduced. Unlike [`Instance::log_encode`],
+    /// all coefficients are one, which keeps the energy landscape smooth at the cost of more decision variables.
+    /// Since a value of `x` corresponds to many states of `b_i`, solvers may prefer adding `b_{i+1} - b_i <= 0` to break the symmetry.
+    ///
+    /// This does not substitute `x` by the returned expression as [`Instance::log_encode`].
+    pub fn unary_encode(&mut self, id: u64) -> Result<Linear> {
+        self.mutate_checked(|instance| {
+            let (lower, upper) = instance.integer_bound_for_encoding(id)?;
+            let start = instance.next_variable_id();
+            let mut terms = Vec::new();
+            for i in 0..(upper - lower) as usize {
+                let b = start + i as u64;
+                instance.push_encoding_binary(b, AuxiliaryRole::UnaryEncode, id, i);
+                terms.push((b, 1.0));
+            }
+            Ok(Linear::new(terms.into_iter(), lower))
+        })
+    }
+
+    /// Encode the integer decision variable `x` of `id` by one-hot binary ones, and returns the expression `x = sum_i (l + i) b_i`
+    ///
+    /// For the bound `[l, u]` of `x` rounded to integers, `u - l + 1` binary decision variables `b_i`
+    /// named `ommx.domain_encode` with subscripts `[id, i]` are introduced, where `b_i = 1` means `x = l + i`.
+    /// The one-hot constraint `sum_i b_i - 1 = 0` named `ommx.domain_encode` is also added.
+    ///
+    /// This does not substitute `x` by the returned expression as [`Instance::log_encode`].
+    pub fn domain_encode(&mut self, id: u64) -> Result<Linear> {
+        self.mutate_checked(|instance| {
+            let (lower, upper) = instance.integer_bound_for_encoding(id)?;
+            let start = instance.next_variable_id();
+            let mut terms = Vec::new();
+            for i in 0..=(upper - lower) as usize {
+                let b = start + i as u64;
+                instance.push_encoding_binary(b, AuxiliaryRole::DomainEncode, id, i);
+                terms.push((b, lower + i as f64));
+            }
+            instance.constraints.push(Constraint {
+                id: instance.next_constraint_id(),
+                equality: Equality::EqualToZero as i32,
+                function: Some(Linear::new(terms.iter().map(|(b, _)| (*b, 1.0)), -1.0).into()),
+                name: Some(AuxiliaryRole::DomainEncode.name().to_string()),
+                ..Default::default()
+            });
+            Ok(Linear::new(terms.into_iter(), 0.0))
+        })
+    }
+
+    /// Encode the integer decision variable of `id` by binary ones with `encoding`, and returns the expression of it
+    ///
+    /// ```rust
+    /// use ommx::{Evaluate, IntegerEncoding, v1::{decision_variable::Kind, Bound, DecisionVariable, Instance, State}};
+    /// use std::collections::HashMap;
+    ///
+    /// for encoding in [IntegerEncoding::Log, IntegerEncoding::Unary, IntegerEncoding::Domain] {
+    ///     let mut instance = Instance {
+    ///         decision_variables: vec![DecisionVariable {
+    ///             id: 0,
+    ///             kind: Kind::Integer as i32,
+    ///             bound: Some(Bound { lower: -1.0, upper: 3.5 }),
+    ///             ..Default::default()
+    ///         }],
+    ///         ..Default::default()
+    ///     };
+    ///     let expr = instance.encode_integer(0, encoding).unwrap();
+    ///     let binaries: Vec<u64> = expr.terms.iter().map(|t| t.id).collect();
+    ///
+    ///     // All binaries zero give the lower bound, except for the domain encoding where it is infeasible
+    ///     let zeros: State = binaries.iter().map(|id| (*id, 0.0)).collect::<HashMap<_, _>>().into();
+    ///     if encoding != IntegerEncoding::Domain {
+    ///         assert_eq!(expr.evaluate(&zeros).unwrap().0, -1.0);
+    ///     }
+    ///     // The upper bound is attained by all ones, or the last one for the domain encoding
+    ///     let upper: State = binaries
+    ///         .iter()
+    ///         .enumerate()
+    ///         .map(|(i, id)| (*id, if encoding != IntegerEncoding::Domain || i + 1 == binaries.len() { 1.0 } else { 0.0 }))
+    ///         .collect::<HashMap<_, _>>()
+    ///         .into();
+    ///     assert_eq!(expr.evaluate(&upper).unwrap().0, 3.0);
+    /// }
+    /// ```
+    pub fn encode_integer(&mut self, id: u64, encoding: IntegerEncoding) -> Result<Linear> {
+        match encoding {
+            IntegerEncoding::Log => self.log_encode(id),
+            IntegerEncoding::Unary => self.unary_encode(id),
+            IntegerEncoding::Domain => self.domain_encode(id),
+        }
+    }
+
+    /// Bound of the integer decision variable of `id` rounded to integers, which must be finite and non-empty
+    fn integer_bound_for_encoding(&self, id: u64) -> Result<(f64, f64)> {
+        let v = self
+            .decision_variables
+            .iter()
+            .find(|v| v.id == id)
+            .with_context(|| format!("Decision variable ({id}) is not found"))?;
+        ensure!(
+            v.kind() == Kind::Integer,
+            "Decision variable ({id}) is not integer: {:?}",
+            v.kind()
+        );
+        let bound = v
+            .bound
+            .as_ref()
+            .with_context(|| format!("Bound of decision variable ({id}) is not set"))?;
+        let (lower, upper) = (bound.lower.ceil(), bound.upper.floor());
+        ensure!(
+            lower.is_finite() && upper.is_finite(),
+            "Bound of decision variable ({id}) is not finite: [{}, {}]",
+            bound.lower,
+            bound.upper
+        );
+        ensure!(
+            lower <= upper,
+            "Bound of decision variable ({id}) has no integer: [{}, {}]",
+            bound.lower,
+            bound.upper
+        );
+        Ok((lower, upper))
+    }
+
+    /// Add the `i`-th binary decision variable of `id` encoding the integer decision variable `encoded`
+    fn push_encoding_binary(&mut self, id: u64, role: AuxiliaryRole, encoded: u64, i: usize) {
+        self.decision_variables.push(DecisionVariable {
+            id,
+            kind: Kind::Binary as i32,
+            bound: Some(Bound {
+                lower: 0.0,
+                upper: 1.0,
+            }),
+            name: Some(role.name().to_string()),
+            subscripts: vec![encoded as i64, i as i64],
+            ..Default::default()
+        });
+    }
+
+    /// [`Instance::log_encode`] the integer decision variable of `id`, and substitute it by the binary expansion
+    /// in the objective and all constraints
+    ///
+    /// The original decision variable is kept in the instance, but no longer used.
+    pub fn log_encode_and_substitute(&mut self, id: u64) -> Result<()> {
+        self.mutate_checked(|instance| {
+            let expr: Function = instance.log_encode(id)?.into();
+            if let Some(objective) = instance.objective.as_mut() {
+                *objective = objective.substitute(id, &expr);
+            }
+            for c in &mut instance.constraints {
+                if let Some(f) = c.function.as_mut() {
+                    *f = f.substitute(id, &expr);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Eliminate the decision variable `x` of `variable_id` by the equality constraint `a x + g(y) = 0` of `constraint_id`,
+    /// and returns the expression `x(y) = -g(y) / a` to recover the value of `x`
+    ///
+    /// `x` is substituted by `x(y)` in the objective and the other constraints, and then `x` and the constraint are removed.
+    /// The bound `[l, u]` of `x` is kept by adding constraints `l - x(y) <= 0` and `x(y) - u <= 0`, unless the range of `x(y)`
+    /// evaluated by [`Function::evaluate_interval`] on the bounds of `y` is already in the bound within `atol`.
+    ///
+    /// This fails if `x` appears in a nonlinear term of the constraint, `|a| <= atol`, or `x` is binary or integer
+    /// but `x(y)` may not be an integer, i.e. `y` has a non-integer decision variable or a coefficient is not an integer within `atol`.
+    /// Semi-continuous and semi-integer `x` are not supported.
+    pub fn eliminate_via_equality(
+        &mut self,
+        constraint_id: u64,
+        variable_id: u64,
+        atol: f64,
+    ) -> Result<Function> {
+        self.mutate_checked(|instance| {
+            let constraint = instance
+                .constraints
+                .iter()
+                .find(|c| c.id == constraint_id)
+                .with_context(|| format!("Constraint ({constraint_id}) is not found"))?;
+            ensure!(
+                constraint.equality() == Equality::EqualToZero,
+                "Constraint ({constraint_id}) is not an equality"
+            );
+            let function = constraint
+                .function
+                .as_ref()
+                .with_context(|| format!("Function of constraint ({constraint_id}) is not set"))?;
+            let mut a = 0.0;
+            let mut rest = BTreeMap::new();
+            for (ids, coefficient) in Polynomial::from(function.clone()).monomials() {
+                if ids == [variable_id] {
+                    a += coefficient;
+                    continue;
+                }
+                ensure!(
+                    !ids.contains(&variable_id),
+                    "Decision variable ({variable_id}) appears in a nonlinear term of constraint ({constraint_id})"
+                );
+                rest.insert(ids, coefficient);
+            }
+            ensure!(
+                a.abs() > atol,
+                "Coefficient of decision variable ({variable_id}) in constraint ({constraint_id}) is zero within {atol}: {a}"
+            );
+            let expr =
+                Function::from_polynomial_in_lowest_form(Polynomial::from_terms(rest) * (-1.0 / a));
+
+            let kinds: BTreeMap<u64, Kind> = instance
+                .decision_variables
+                .iter()
+                .map(|v| (v.id, v.kind()))
+                .collect();
+            match kinds.get(&variable_id) {
+                Some(Kind::Continuous) => {}
+                Some(Kind::Binary | Kind::Integer) => {
+                    for (ids, coefficient) in Polynomial::from(expr.clone()).monomials() {
+                        ensure!(
+                            (coefficient - coefficient.round()).abs() <= atol,
+                            "Decision variable ({variable_id}) is integer, but the coefficient of {ids:?} in its expression is not an integer: {coefficient}"
+                        );
+                        for id in ids {
+                            ensure!(
+                                matches!(kinds.get(&id), Some(Kind::Binary | Kind::Integer)),
+                                "Decision variable ({variable_id}) is integer, but its expression depends on a non-integer decision variable ({id})"
+                            );
+                        }
+                    }
+                }
+                Some(kind) => bail!("{kind:?} decision variable ({variable_id}) is not supported"),
+                None => bail!("Decision variable ({variable_id}) is not found"),
+            }
+
+            let intervals = instance.variable_intervals();
+            let bound = &intervals[&variable_id];
+            let range = expr.evaluate_interval(&intervals);
+            let x: Function = Linear::single_term(variable_id, 1.0).into();
+            let mut bound_functions = Vec::new();
+            if bound.lower.is_finite() && range.lower < bound.lower - atol {
+                bound_functions.push(Function::from(bound.lower) - x.clone());
+            }
+            if bound.upper.is_finite() && range.upper > bound.upper + atol {
+                bound_functions.push(x - Function::from(bound.upper));
+            }
+
+            let next_constraint_id = instance.next_constraint_id();
+            instance.constraints.retain(|c| c.id != constraint_id);
+            instance.decision_variables.retain(|v| v.id != variable_id);
+            for (id, f) in (next_constraint_id..).zip(bound_functions) {
+                instance.constraints.push(Constraint {
+                    id,
+                    equality: Equality::LessThanOrEqualToZero as i32,
+                    function: Some(f),
+                    ..Default::default()
+                });
+            }
+            if let Some(objective) = instance.objective.as_mut() {
+                *objective = objective.substitute(variable_id, &expr);
+            }
+            for c in &mut instance.constraints {
+                if let Some(f) = c.function.as_mut() {
+                    *f = f.substitute(variable_id, &expr);
+                }
+            }
+            Ok(expr)
+        })
+    }
+
+    /// Interaction graph of decision variables as a list of nodes and weighted edges `(i, j, weight)` with `i < j`
+    ///
+    /// Nodes are the decision variables defined or used in this instance.
+    /// Two decision variables are connected if they appear together in a constraint,
+    /// or in a same term of the objective, e.g. `x1 * x2`.
+    /// The weight is the number of such co-occurrences.
+    pub fn interaction_graph(&self) -> (Vec<u64>, Vec<(u64, u64, usize)>) {
+        let mut nodes: BTreeSet<u64> = self.decision_variables.iter().map(|v| v.id).collect();
+        nodes.extend(self.used_decision_variable_ids());
+
+        let mut edges: BTreeMap<(u64, u64), usize> = BTreeMap::new();
+        let mut connect = |ids: BTreeSet<u64>| {
+            for (i, j) in ids.iter().tuple_combinations() {
+                *edges.entry((*i, *j)).or_default() += 1;
+            }
+        };
+        if let Some(objective) = &self.objective {
+            for ids in Polynomial::from(objective.clone()).monomials().into_keys() {
+                connect(ids.into_iter().collect());
+            }
+        }
+        for c in &self.constraints {
+            if let Some(f) = &c.function {
+                connect(f.used_decision_variable_ids());
+            }
+        }
+
+        (
+            nodes.into_iter().collect(),
+            edges
+                .into_iter()
+                .map(|((i, j), weight)| (i, j, weight))
+                .collect(),
+        )
+    }
+
+    /// Split into independent subproblems along the connected components of [`Instance::interaction_graph`]
+    ///
+    /// Each subproblem has the decision variables of a component, the constraints on them, and the terms of the objective on them,
+    /// so that the sum of objectives of subproblems equals the original objective.
+    /// The constant term of the objective and constraints without decision variables belong to the first subproblem.
+    /// Subproblems are sorted by the smallest decision variable ID.
+    pub fn connected_subproblems(&self) -> Vec<Instance> {
+        let (nodes, edges) = self.interaction_graph();
+        if nodes.is_empty() {
+            return vec![self.clone()];
+        }
+        let index: BTreeMap<u64, usize> =
+            nodes.iter().enumerate().map(|(n, id)| (*id, n)).collect();
+        let mut parent: Vec<usize> = (0..nodes.len()).collect();
+        fn root(parent: &mut [usize], mut n: usize) -> usize {
+            while parent[n] != n {
+                parent[n] = parent[parent[n]];
+                n = parent[n];
+            }
+            n
+        }
+        for (i, j, _) in edges {
+            let (i, j) = (root(&mut parent, index[&i]), root(&mut parent, index[&j]));
+            // Keep the smaller index as the root to sort components by the smallest ID
+            parent[i.max(j)] = i.min(j);
+        }
+        let component: BTreeMap<u64, usize> = nodes
+            .iter()
+            .map(|id| (*id, root(&mut parent, index[id])))
+            .collect();
+        let roots: BTreeSet<usize> = component.values().cloned().collect();
+        let first = roots.first().cloned().unwrap_or_default();
+
+        // Monomials are connected, so checking the first ID is enough
+        let component_of = |ids: &[u64]| ids.first().map_or(first, |id| component[id]);
+        roots
+            .into_iter()
+            .map(|root| {
+                let keep = |ids: &[u64]| component_of(ids) == root;
+                Instance {
+                    description: self.description.clone(),
+                    decision_variables: self
+                        .decision_variables
+                        .iter()
+                        .filter(|v| component[&v.id] == root)
+                        .cloned()
+                        .collect(),
+                    objective: self.objective.as_ref().map(|f| f.restrict(keep)),
+                    constraints: self
+                        .constraints
+                        .iter()
+                        .filter(|c| {
+                            let ids: Vec<u64> = c
+                                .function
+                                .as_ref()
+                                .map(|f| f.used_decision_variable_ids().into_iter().collect())
+                                .unwrap_or_default();
+                            keep(&ids)
+                        })
+                        .cloned()
+                        .collect(),
+                    sense: self.sense,
+                }
+            })
+            .collect()
+    }
+
+    /// Reduce the objective of binary decision variables into degree two or lower by introducing auxiliary binary variables.
+    ///
+    /// This repeatedly picks the most frequent pair `(x_i, x_j)` in the terms of degree three or higher,
+    /// and replaces the product `x_i x_j` in these terms by a new binary variable `y` named `ommx.reduce_to_quadratic`
+    /// with subscripts `[i, j]`. To enforce `y = x_i x_j`, the AND-gadget penalty `P (x_i x_j - 2 x_i y - 2 x_j y + 3 y)`
+    /// is added to the objective (subtracted for maximization), which is zero if `y = x_i x_j` and at least `P` otherwise.
+    /// `penalty_weight` `P` must be large enough compared to the objective to keep the optimal solution.
+    /// Terms whose coefficients are within `atol` of zero after merging `x^k = x` are dropped,
+    /// so that cancelled terms do not introduce auxiliary variables. Constraints are not modified.
+    pub fn reduce_to_quadratic(&mut self, penalty_weight: f64, atol: f64) -> Result<()> {
+        ensure!(
+            penalty_weight > 0.0,
+            "Penalty weight must be positive: {penalty_weight}"
+        );
+        let sign = match self.sense() {
+            Sense::Minimize => 1.0,
+            Sense::Maximize => -1.0,
+            Sense::Unspecified => bail!("Sense is not specified"),
+        };
+        let objective = self.objective.as_ref().context("Objective is not set")?;
+        let kinds: BTreeMap<u64, Kind> = self
+            .decision_variables
+            .iter()
+            .map(|v| (v.id, v.kind()))
+            .collect();
+        for id in objective.used_decision_variable_ids() {
+            ensure!(
+                kinds.get(&id) == Some(&Kind::Binary),
+                "Decision variable ({id}) in the objective is not a binary variable"
+            );
+        }
+
+        // `x^k = x` for binary variables
+        let mut terms: BTreeMap<Vec<u64>, f64> = BTreeMap::new();
+        for (mut ids, coefficient) in Polynomial::from(objective.clone()).monomials() {
+            ids.dedup();
+            *terms.entry(ids).or_insert(0.0) += coefficient;
+        }
+        terms.retain(|_, coefficient| coefficient.abs() > atol);
+
+        self.mutate_checked(|instance| {
+            let mut next_id = instance.next_variable_id();
+            loop {
+                let mut pairs: BTreeMap<(u64, u64), usize> = BTreeMap::new();
+                for ids in terms.keys().filter(|ids| ids.len() >= 3) {
+                    for (i, j) in ids.iter().tuple_combinations() {
+                        *pairs.entry((*i, *j)).or_default() += 1;
+                    }
+                }
+                // `max_by_key` returns the last maximum, so reverse to pick the smallest pair among them
+                let Some((i, j)) = pairs
+                    .into_iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .map(|(pair, _)| pair)
+                else {
+                    break;
+                };
+
+                let y = next_id;
+                next_id += 1;
+                instance.decision_variables.push(DecisionVariable {
+                    id: y,
+                    kind: Kind::Binary as i32,
+                    name: Some(AuxiliaryRole::ReduceToQuadratic.name().to_string()),
+                    subscripts: vec![i as i64, j as i64],
+                    ..Default::default()
+                });
+
+                let mut reduced = BTreeMap::new();
+                for (mut ids, coefficient) in std::mem::take(&mut terms) {
+                    if ids.len() >= 3 && ids.contains(&i) && ids.contains(&j) {
+                        ids.retain(|id| *id != i && *id != j);
+                        ids.push(y);
+                        ids.sort_unstable();
+                    }
+                    *reduced.entry(ids).or_insert(0.0) += coefficient;
+                }
+                for (ids, coefficient) in [
+                    (vec![i, j], 1.0),
+                    (vec![i, y], -2.0),
+                    (vec![j, y], -2.0),
+                    (vec![y], 3.0),
+                ] {
+                    *reduced.entry(ids).or_insert(0.0) += sign * penalty_weight * coefficient;
+                }
+                reduced.retain(|_, coefficient| coefficient.abs() > atol);
+                terms = reduced;
+            }
+
+            instance.objective = Some(Quadratic::try_from(Polynomial::from_terms(terms))?.into());
+            Ok(())
+        })
+    }
+
+    /// Convert into a minimization problem by negating the objective if the sense is maximize
+    ///
+    /// Returns `true` if the objective is negated.
+    pub fn as_minimization_problem(&mut self) -> bool {
+        if self.sense() != Sense::Maximize {
+            return false;
+        }
+        self.objective = self.objective.take().map(|f| -f);
+        self.set_sense(Sense::Minimize);
+        true
+    }
+
+    /// Get the objective as a QUBO `sum_{i <= j} Q_{ij} x_i x_j + constant` to be minimized
+    ///
+    /// Linear terms are stored in the diagonal since `x^2 = x` for binary variables.
+    /// This fails for a maximization problem instead of silently negating the objective;
+    /// use [`Instance::as_qubo_format_auto`] or [`Instance::as_minimization_problem`] for it.
+    /// This also fails if there are constraints, non-binary decision variables in the objective, or terms of degree three or higher.
+    #[allow(clippy::type_complexity)]
+    pub fn as_qubo_format(&self) -> Result<(BTreeMap<(u64, u64), f64>, f64)> {
+        let mut quad = BTreeMap::new();
+        let mut constant = 0.0;
+        for (ids, coefficient) in self.binary_monomials("QUBO")? {
+            match ids.as_slice() {
+                [] => constant += coefficient,
+                [i] => *quad.entry((*i, *i)).or_insert(0.0) += coefficient,
+                [i, j] => *quad.entry((*i, *j)).or_insert(0.0) += coefficient,
+                _ => bail!("QUBO cannot have a term of degree {}: {ids:?}", ids.len()),
+            }
+        }
+        Ok((quad, constant))
+    }
+
+    /// [`Instance::as_qubo_format`] accepting a maximization problem, with a flag which is `true` if the objective is negated
+    ///
+    /// The objective of a maximization problem is negated on a clone, see [`Instance::as_minimization_problem`],
+    /// and thus the QUBO is always to be minimized. If the flag is `true`, the objective value of this instance is the negation of
+    /// the energy `sum_{i <= j} Q_{ij} x_i x_j + constant`, and the caller should flip the sign of the energy reported by solvers.
+    ///
+    /// ```rust
+    /// use ommx::v1::{instance::Sense, Instance};
+    /// use maplit::btreemap;
+    ///
+    /// let instance = Instance::from_qubo(&btreemap! { (0, 0) => 1.0, (0, 1) => -2.0 }, 3.0, Sense::Maximize).unwrap();
+    /// let (quad, constant, negated) = instance.as_qubo_format_auto().unwrap();
+    /// assert!(negated);
+    /// assert_eq!(quad, btreemap! { (0, 0) => -1.0, (0, 1) => 2.0 });
+    /// assert_eq!(constant, -3.0);
+    ///
+    /// // `as_qubo_format` is strict about the sense
+    /// assert!(instance.as_qubo_format().is_err());
+    /// let mut minimization = instance.clone();
+    /// minimization.as_minimization_problem();
+    /// assert_eq!(minimization.as_qubo_format().unwrap(), (quad, constant));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn as_qubo_format_auto(&self) -> Result<(BTreeMap<(u64, u64), f64>, f64, bool)> {
+        let mut instance = self.clone();
+        let negated = instance.as_minimization_problem();
+        let (quad, constant) = instance.as_qubo_format()?;
+        Ok((quad, constant, negated))
+    }
+
+    /// Get the objective as a HUBO `sum_{S} c_S prod_{i in S} x_i + constant` to be minimized
+    ///
+    /// This is the inverse of [`Instance::from_hubo`] for a minimization problem.
+    /// This fails for a maximization problem as [`Instance::as_qubo_format`] does, see [`Instance::as_hubo_format_auto`] for it.
+    /// This also fails if there are constraints or non-binary decision variables in the objective.
+    #[allow(clippy::type_complexity)]
+    pub fn as_hubo_format(&self) -> Result<(BTreeMap<BTreeSet<u64>, f64>, f64)> {
+        let mut terms = BTreeMap::new();
+        let mut constant = 0.0;
+        for (ids, coefficient) in self.binary_monomials("HUBO")? {
+            if ids.is_empty() {
+                constant += coefficient;
+            } else {
+                *terms.entry(ids.into_iter().collect()).or_insert(0.0) += coefficient;
+            }
+        }
+        Ok((terms, constant))
+    }
+
+    /// [`Instance::as_hubo_format`] accepting a maximization problem, with a flag which is `true` if the objective is negated
+    ///
+    /// The sign convention is the same as [`Instance::as_qubo_format_auto`].
+    ///
+    /// ```rust
+    /// use ommx::v1::{instance::Sense, Instance};
+    /// use maplit::{btreemap, btreeset};
+    ///
+    /// let instance = Instance::from_hubo(&btreemap! { btreeset! { 0, 1, 2 } => 2.0, btreeset! { 1 } => -1.0 }, 0.5, Sense::Maximize).unwrap();
+    /// assert!(instance.as_hubo_format().is_err());
+    /// let (terms, constant, negated) = instance.as_hubo_format_auto().unwrap();
+    /// assert!(negated);
+    /// assert_eq!(terms, btreemap! { btreeset! { 0, 1, 2 } => -2.0, btreeset! { 1 } => 1.0 });
+    /// assert_eq!(constant, -0.5);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn as_hubo_format_auto(&self) -> Result<(BTreeMap<BTreeSet<u64>, f64>, f64, bool)> {
+        let mut instance = self.clone();
+        let negated = instance.as_minimization_problem();
+        let (terms, constant) = instance.as_hubo_format()?;
+        Ok((terms, constant, negated))
+    }
+
+    /// Monomials of the objective where `x^k` is replaced by `x` for binary variables
+    ///
+    /// This fails for a maximization problem, or if there are constraints or non-binary decision variables in the objective.
+    /// `format` is used in error messages.
+    fn binary_monomials(&self, format: &str) -> Result<BTreeMap<Vec<u64>, f64>> {
+        ensure!(
+            self.sense() != Sense::Maximize,
+            "{format} is to be minimized, but the sense is maximize. Use `as_{}_format_auto` or negate the objective by `as_minimization_problem`",
+            format.to_lowercase()
+        );
+        ensure!(
+            self.constraints.is_empty(),
+            "{format} cannot have constraints, but {} constraints exist",
+            self.constraints.len()
+        );
+        let objective = self.objective.clone().context("Objective is not set")?;
+
+        let binary_ids: BTreeSet<u64> = self
+            .decision_variables
+            .iter()
+            .filter(|v| v.kind() == Kind::Binary)
+            .map(|v| v.id)
+            .collect();
+        for id in objective.used_decision_variable_ids() {
+            ensure!(
+                binary_ids.contains(&id),
+                "Decision variable ({id}) in the objective is not a binary variable"
+            );
+        }
+
+        let mut poly = Polynomial::from(objective);
+        poly.simplify_binary_squares(&binary_ids);
+        Ok(poly.monomials())
+    }
+}
+
+/// Test the positive semidefiniteness of the Hessian of `sum_{(i, j)} c_ij x_i x_j` given as `(i, j) -> c_ij`,
+/// see [`Instance::is_convex_qp`] for `atol`
+pub(crate) fn is_hessian_positive_semidefinite(
+    quad: &BTreeMap<(u64, u64), f64>,
+    atol: f64,
+) -> bool {
+    let index: BTreeMap<u64, usize> = quad
+        .keys()
+        .flat_map(|(i, j)| [*i, *j])
+        .collect::<BTreeSet<u64>>()
+        .into_iter()
+        .enumerate()
+        .map(|(n, id)| (id, n))
+        .collect();
+    let mut hessian = vec![vec![0.0; index.len()]; index.len()];
+    for ((i, j), coefficient) in quad {
+        let (i, j) = (index[i], index[j]);
+        hessian[i][j] += coefficient;
+        hessian[j][i] += coefficient;
+    }
+    is_positive_semidefinite(hessian, atol)
+}
+
+/// Test the positive semidefiniteness of a symmetric matrix by the Cholesky decomposition with symmetric pivoting
+fn is_positive_semidefinite(mut a: Vec<Vec<f64>>, atol: f64) -> bool {
+    let mut remaining: Vec<usize> = (0..a.len()).collect();
+    while let Some((pos, k)) = remaining
+        .iter()
+        .cloned()
+        .enumerate()
+        .max_by(|(_, i), (_, j)| a[*i][*i].total_cmp(&a[*j][*j]))
+    {
+        let pivot = a[k][k];
+        if pivot <= atol {
+            // The remaining diagonal entries are almost zero or negative, and thus
+            // the remaining submatrix is semidefinite only if it is almost zero.
+            return remaining.iter().all(|&i| {
+                remaining.iter().all(|&j| {
+                    if i == j {
+                        a[i][i] >= -atol
+                    } else {
+                        a[i][j].abs() <= atol
+                    }
+                })
+            });
+        }
+        remaining.swap_remove(pos);
+        for &i in &remaining {
+            for &j in &remaining {
+                a[i][j] -= a[i][k] * a[k][j] / pivot;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        v1::{function::Function as FunctionEnum, State},
+        Evaluate,
+    };
+    use maplit::{btreemap, hashmap};
+    use std::collections::HashMap;
+
+    fn binaries(ids: impl IntoIterator<Item = u64>) -> Vec<DecisionVariable> {
+        ids.into_iter()
+            .map(|id| DecisionVariable {
+                id,
+                kind: Kind::Binary as i32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Optimal value and state found by enumerating all the states of binary decision variables
+    fn brute_force(instance: &Instance) -> (f64, State) {
+        let ids: Vec<u64> = instance.decision_variables.iter().map(|v| v.id).collect();
+        let sign = if instance.sense() == Sense::Maximize {
+            -1.0
+        } else {
+            1.0
+        };
+        let mut best: Option<(f64, State)> = None;
+        for bits in 0..(1u64 << ids.len()) {
+            let state: State = ids
+                .iter()
+                .enumerate()
+                .map(|(i, id)| (*id, ((bits >> i) & 1) as f64))
+                .collect::<HashMap<_, _>>()
+                .into();
+            let (value, _) = instance
+                .objective
+                .as_ref()
+                .unwrap()
+                .evaluate(&state)
+                .unwrap();
+            if best.as_ref().is_none_or(|(b, _)| sign * value < sign * b) {
+                best = Some((value, state));
+            }
+        }
+        best.unwrap()
+    }
+
+    /// Values of the decision variables of `ids` in `state`
+    fn restrict_state(state: &State, ids: impl IntoIterator<Item = u64>) -> BTreeMap<u64, f64> {
+        ids.into_iter().map(|id| (id, state.entries[&id])).collect()
+    }
+
+    #[test]
+    fn connected_subproblems_recombine() {
+        let objective = Quadratic::from_terms(
+            btreemap! { (0, 1) => 2.0, (1, 2) => 0.5, (3, 4) => -1.0 },
+            Some(Linear::new(
+                [(0, 1.0), (4, 3.0), (5, -2.0)].into_iter(),
+                7.0,
+            )),
+        );
+        let constraint = |id, function: Function| Constraint {
+            id,
+            equality: Equality::LessThanOrEqualToZero as i32,
+            function: Some(function),
+            ..Default::default()
+        };
+        let instance = Instance {
+            decision_variables: (0..6)
+                .map(|id| DecisionVariable {
+                    id,
+                    kind: Kind::Continuous as i32,
+                    ..Default::default()
+                })
+                .collect(),
+            objective: Some(objective.into()),
+            constraints: vec![
+                constraint(
+                    0,
+                    Linear::new([(0, 1.0), (2, 1.0)].into_iter(), -1.0).into(),
+                ),
+                constraint(
+                    1,
+                    Quadratic::from_terms(btreemap! { (3, 4) => 1.0 }, None).into(),
+                ),
+                // Constant-only constraint
+                constraint(2, Function::from(-1.0)),
+            ],
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        };
+
+        let subproblems = instance.connected_subproblems();
+        let components: Vec<Vec<u64>> = subproblems
+            .iter()
+            .map(|p| p.decision_variables.iter().map(|v| v.id).collect())
+            .collect();
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+
+        // Constraints are partitioned, and the constant-only one goes to the first subproblem
+        let constraint_ids: Vec<Vec<u64>> = subproblems
+            .iter()
+            .map(|p| p.constraints.iter().map(|c| c.id).collect())
+            .collect();
+        assert_eq!(constraint_ids, vec![vec![0, 2], vec![1], vec![]]);
+
+        let state: State = HashMap::from([
+            (0, 1.5),
+            (1, -2.0),
+            (2, 0.25),
+            (3, 3.0),
+            (4, -0.5),
+            (5, 4.0),
+        ])
+        .into();
+        let (original, _) = instance
+            .objective
+            .as_ref()
+            .unwrap()
+            .evaluate(&state)
+            .unwrap();
+        let mut total = 0.0;
+        for (n, p) in subproblems.iter().enumerate() {
+            // Each subproblem is evaluated only with the values of its own decision variables
+            let restricted: State = restrict_state(&state, components[n].iter().cloned())
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+                .into();
+            let (value, _) = p.objective.as_ref().unwrap().evaluate(&restricted).unwrap();
+            total += value;
+            for c in &p.constraints {
+                c.evaluate(&restricted).unwrap();
+            }
+            // The constant term belongs to the first subproblem
+            assert_eq!(p.objective_constant(), if n == 0 { 7.0 } else { 0.0 });
+        }
+        assert_eq!(total, original);
+    }
+
+    #[test]
+    fn encode_integer_ranges_over_bound() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand_xoshiro::Xoshiro256StarStar::seed_from_u64(0);
+        for _ in 0..30 {
+            let lower: f64 = rng.gen_range(-10.0..10.0);
+            let upper = lower + rng.gen_range(0.0..10.0);
+            let (l, u) = (lower.ceil(), upper.floor());
+            if l > u {
+                continue;
+            }
+            for (encoding, role) in [
+                (IntegerEncoding::Log, AuxiliaryRole::LogEncode),
+                (IntegerEncoding::Unary, AuxiliaryRole::UnaryEncode),
+                (IntegerEncoding::Domain, AuxiliaryRole::DomainEncode),
+            ] {
+                let mut instance = Instance {
+                    decision_variables: vec![DecisionVariable {
+                        id: 0,
+                        kind: Kind::Integer as i32,
+                        bound: Some(Bound { lower, upper }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                };
+                let expr = instance.encode_integer(0, encoding).unwrap();
+                let binaries: Vec<u64> = instance.auxiliary_variables(role);
+                assert_eq!(binaries.len(), instance.decision_variables.len() - 1);
+                for (i, v) in instance.decision_variables[1..].iter().enumerate() {
+                    assert_eq!(v.kind(), Kind::Binary);
+                    assert_eq!(v.subscripts, vec![0, i as i64]);
+                }
+                let one_hot = encoding == IntegerEncoding::Domain;
+                if one_hot {
+                    assert_eq!(instance.constraints.len(), 1);
+                    assert_eq!(instance.constraints[0].name.as_deref(), Some(role.name()));
+                } else {
+                    assert!(instance.constraints.is_empty());
+                }
+
+                let evaluate = |values: &[f64]| -> f64 {
+                    let state: State = binaries
+                        .iter()
+                        .cloned()
+                        .zip(values.iter().cloned())
+                        .collect::<HashMap<_, _>>()
+                        .into();
+                    expr.evaluate(&state).unwrap().0
+                };
+                // Extreme assignments attain the bounds exactly
+                let n = binaries.len();
+                let (min, max) = if one_hot {
+                    let hot = |k: usize| {
+                        (0..n)
+                            .map(|i| if i == k { 1.0 } else { 0.0 })
+                            .collect::<Vec<_>>()
+                    };
+                    (hot(0), hot(n - 1))
+                } else {
+                    (vec![0.0; n], vec![1.0; n])
+                };
+                assert_eq!(evaluate(&min), l, "{encoding:?} [{lower}, {upper}]");
+                assert_eq!(evaluate(&max), u, "{encoding:?} [{lower}, {upper}]");
+
+                // Every feasible assignment stays in the bound, and every integer in it is reachable
+                let mut reached = BTreeSet::new();
+                for mask in 0..(1_u64 << n) {
+                    let values: Vec<f64> = (0..n).map(|i| ((mask >> i) & 1) as f64).collect();
+                    if one_hot && values.iter().sum::<f64>() != 1.0 {
+                        continue;
+                    }
+                    let value = evaluate(&values);
+                    assert!(
+                        l <= value && value <= u,
+                        "{encoding:?} gives {value} out of [{l}, {u}]"
+                    );
+                    reached.insert(value as i64);
+                }
+                assert_eq!(reached, (l as i64..=u as i64).collect());
+            }
+        }
+    }
+
+    #[test]
+    fn reduce_to_quadratic_keeps_optimum() {
+        // Terms of degree three and four sharing pairs, and `x1^2 x2 x3 = x1 x2 x3`
+        let terms = btreemap! {
+            vec![] => 0.5,
+            vec![0] => 1.0,
+            vec![2, 3] => 1.0,
+            vec![0, 1, 2] => -3.0,
+            vec![1, 1, 2, 3] => 2.0,
+            vec![0, 1, 2, 3] => -4.0,
+            vec![0, 2, 3] => 0.0,
+        };
+        for sense in [Sense::Minimize, Sense::Maximize] {
+            let sign = if sense == Sense::Minimize { 1.0 } else { -1.0 };
+            let objective: Function = Polynomial::from_terms(
+                terms
+                    .iter()
+                    .map(|(ids, c)| (ids.clone(), sign * c))
+                    .collect(),
+            )
+            .into();
+            let hubo = Instance {
+                decision_variables: binaries(0..4),
+                objective: Some(objective),
+                sense: sense as i32,
+                ..Default::default()
+            };
+            let mut qubo = hubo.clone();
+            qubo.reduce_to_quadratic(10.0, 1e-9).unwrap();
+            assert!(matches!(
+                qubo.objective.as_ref().unwrap().function,
+                Some(FunctionEnum::Quadratic(_))
+            ));
+            // `x0 x1` and `x2 x3` are substituted, and the zero term `x0 x2 x3` introduces nothing
+            assert_eq!(
+                qubo.decision_variables
+                    .iter()
+                    .filter(|v| v.name() == "ommx.reduce_to_quadratic")
+                    .count(),
+                2
+            );
+
+            let (hubo_value, hubo_state) = brute_force(&hubo);
+            let (qubo_value, qubo_state) = brute_force(&qubo);
+            assert_eq!(hubo_value, sign * -2.5);
+            assert_eq!(qubo_value, hubo_value);
+            assert_eq!(
+                restrict_state(&qubo_state, 0..4),
+                restrict_state(&hubo_state, 0..4)
+            );
+        }
+    }
+
+    #[test]
+    fn clamp_state_to_bounds_projects_kinds() {
+        let variable = |id, kind: Kind, lower, upper| DecisionVariable {
+            id,
+            kind: kind as i32,
+            bound: Some(Bound { lower, upper }),
+            ..Default::default()
+        };
+        let instance = Instance {
+            decision_variables: vec![
+                variable(0, Kind::Continuous, -1.0, 2.0),
+                variable(1, Kind::Integer, 0.0, 10.0),
+                variable(2, Kind::Integer, 0.5, 3.5),
+                variable(3, Kind::Binary, 0.0, 1.0),
+                variable(4, Kind::SemiContinuous, 1.0, 5.0),
+                variable(5, Kind::SemiContinuous, 1.0, 5.0),
+                variable(6, Kind::SemiContinuous, 1.0, 5.0),
+                variable(7, Kind::SemiInteger, 2.0, 5.0),
+                variable(8, Kind::SemiInteger, 2.0, 5.0),
+            ],
+            ..Default::default()
+        };
+        let state: State = hashmap! {
+            0 => 2.5, 1 => 2.4, 2 => 3.7, 3 => 0.6, 4 => 0.4,
+            5 => 0.6, 6 => 7.0, 7 => 0.9, 8 => 3.6, 9 => 100.0,
+        }
+        .into();
+        let clamped = instance.clamp_state_to_bounds(&state, 1e-6);
+        assert_eq!(
+            clamped.entries,
+            hashmap! {
+                0 => 2.0, 1 => 2.0, 2 => 3.0, 3 => 1.0, 4 => 0.0,
+                5 => 1.0, 6 => 5.0, 7 => 0.0, 8 => 4.0, 9 => 100.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::v1::{
+    instance::{Description, Sense},
+    Constraint, DecisionVariable, Function, Instance,
+};
+use anyhow::Result;
+
+/// Build an [`Instance`] incrementally, and validate it at [`InstanceBuilder::build`]
+///
+/// ```rust
+/// use ommx::{InstanceBuilder, v1::{instance::Sense, DecisionVariable, decision_variable::Kind, Linear}};
+///
+/// let instance = InstanceBuilder::new(Sense::Minimize)
+///     .add_variable(DecisionVariable { id: 1, kind: Kind::Binary as i32, ..Default::default() })
+///     .set_objective(Linear::single_term(1, 2.0))
+///     .build()
+///     .unwrap();
+/// assert_eq!(instance.decision_variables.len(), 1);
+///
+/// // Undefined decision variable is used in the objective
+/// assert!(InstanceBuilder::new(Sense::Minimize)
+///     .set_objective(Linear::single_term(2, 1.0))
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct InstanceBuilder {
+    instance: Instance,
+}
+
+impl InstanceBuilder {
+    /// Start with no decision variables, no constraints, and zero objective
+    pub fn new(sense: Sense) -> Self {
+        Self {
+            instance: Instance {
+                sense: sense as i32,
+                objective: Some(0.0.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn add_variable(mut self, decision_variable: DecisionVariable) -> Self {
+        self.instance.decision_variables.push(decision_variable);
+        self
+    }
+
+    pub fn add_constraint(mut self, constraint: Constraint) -> Self {
+        self.instance.constraints.push(constraint);
+        self
+    }
+
+    pub fn set_objective(mut self, objective: impl Into<Function>) -> Self {
+        self.instance.objective = Some(objective.into());
+        self
+    }
+
+    pub fn set_description(mut self, description: Description) -> Self {
+        self.instance.description = Some(description);
+        self
+    }
+
+    /// Build the instance, which fails if it violates [`Instance::verify_invariants`], e.g. duplicated IDs
+    pub fn build(self) -> Result<Instance> {
+        self.instance.verify_invariants()?;
+        Ok(self.instance)
+    }
+}
@@ -0,0 +1,77 @@
+use crate::v1::{linear::Term, Linear};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+impl Linear {
+    pub fn new(terms: impl Iterator<Item = (u64, f64)>, constant: f64) -> Self {
+        Self {
+            terms: terms
+                .map(|(id, coefficient)| Term { id, coefficient })
+                .collect(),
+            constant,
+        }
+    }
+
+    /// Linear function consisting of a single term `coefficient * x_id`
+    pub fn single_term(id: u64, coefficient: f64) -> Self {
+        Self::new(std::iter::once((id, coefficient)), 0.0)
+    }
+
+    pub fn used_decision_variable_ids(&self) -> BTreeSet<u64> {
+        self.terms.iter().map(|term| term.id).collect()
+    }
+
+    /// Degree of the function, `1` if any term is stored and `0` otherwise. This is `O(1)`.
+    pub fn degree(&self) -> usize {
+        if self.terms.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Number of stored terms including the constant term if it is non-zero. This is `O(1)`.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len() + usize::from(self.constant != 0.0)
+    }
+}
+
+impl Add for Linear {
+    type Output = Self;
+
+    /// Terms of the same ID are merged, and the result is sorted by ID.
+    fn add(self, rhs: Self) -> Self {
+        let mut terms = BTreeMap::new();
+        for Term { id, coefficient } in self.terms.into_iter().chain(rhs.terms) {
+            *terms.entry(id).or_insert(0.0) += coefficient;
+        }
+        Self::new(terms.into_iter(), self.constant + rhs.constant)
+    }
+}
+
+impl Neg for Linear {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+impl Sub for Linear {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul<f64> for Linear {
+    type Output = Self;
+    fn mul(mut self, rhs: f64) -> Self {
+        for term in &mut self.terms {
+            term.coefficient *= rhs;
+        }
+        self.constant *= rhs;
+        self
+    }
+}
@@ -0,0 +1,223 @@
+use crate::v1::{Linear, Monomial, Polynomial, Quadratic};
+use anyhow::{bail, Error, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+impl Polynomial {
+    pub fn used_decision_variable_ids(&self) -> BTreeSet<u64> {
+        self.terms
+            .iter()
+            .flat_map(|term| term.ids.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Degree of the function, the maximum length of stored monomials
+    ///
+    /// This is `O(n)` for `n` stored monomials since the message does not hold the degree.
+    /// A cached degree cannot be attached either, since this type is generated from the protobuf schema
+    /// and every field is serialized, while `terms` is public and can be modified without updating such a cache.
+    /// Keep the result if it is used repeatedly for the same polynomial.
+    /// Use [`Function::degree`](crate::v1::Function::degree) for other forms, which is `O(1)`.
+    pub fn degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|term| term.ids.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of stored monomials including the constant one. This is `O(1)`.
+    pub fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Constant term, i.e. the sum of coefficients of monomials without IDs
+    pub fn constant_term(&self) -> f64 {
+        self.terms
+            .iter()
+            .filter(|term| term.ids.is_empty())
+            .map(|term| term.coefficient)
+            .sum()
+    }
+
+    /// Replace the constant term by `constant`
+    pub fn set_constant_term(&mut self, constant: f64) {
+        self.terms.retain(|term| !term.ids.is_empty());
+        if constant != 0.0 {
+            self.terms.push(Monomial {
+                ids: Vec::new(),
+                coefficient: constant,
+            });
+        }
+    }
+
+    /// Create from monomials keyed by their IDs
+    pub fn from_terms(terms: BTreeMap<Vec<u64>, f64>) -> Self {
+        Self {
+            terms: terms
+                .into_iter()
+                .map(|(ids, coefficient)| Monomial { ids, coefficient })
+                .collect(),
+        }
+    }
+
+    /// Monomials keyed by their sorted IDs, where monomials of the same IDs are merged, e.g. `x1 * x2` and `x2 * x1`
+    pub fn monomials(&self) -> BTreeMap<Vec<u64>, f64> {
+        let mut terms = BTreeMap::new();
+        for term in &self.terms {
+            let mut ids = term.ids.clone();
+            ids.sort_unstable();
+            *terms.entry(ids).or_insert(0.0) += term.coefficient;
+        }
+        terms
+    }
+
+    /// Replace `x_i^k` by `x_i` for binary variables `x_i` of `binary_ids`, since `x^k = x` for `x in {0, 1}`
+    pub fn simplify_binary_squares(&mut self, binary_ids: &BTreeSet<u64>) {
+        let mut terms = BTreeMap::new();
+        for (mut ids, coefficient) in self.monomials() {
+            ids.dedup_by(|a, b| a == b && binary_ids.contains(a));
+            *terms.entry(ids).or_insert(0.0) += coefficient;
+        }
+        *self = Self::from_terms(terms);
+    }
+
+    /// Substitute the decision variable of `id` by `expr`, e.g. `x1^2 * x2` becomes `expr^2 * x2` for `id = 1`
+    pub fn substitute(&self, id: u64, expr: &Polynomial) -> Polynomial {
+        let mut out = Polynomial::default();
+        for (ids, coefficient) in self.monomials() {
+            let (hit, rest): (Vec<u64>, Vec<u64>) = ids.into_iter().partition(|i| *i == id);
+            let mut term = Polynomial::from_terms(BTreeMap::from([(rest, coefficient)]));
+            for _ in hit {
+                term = term * expr.clone();
+            }
+            out = out + term;
+        }
+        out
+    }
+}
+
+impl From<f64> for Polynomial {
+    fn from(constant: f64) -> Self {
+        Self {
+            terms: vec![Monomial {
+                ids: Vec::new(),
+                coefficient: constant,
+            }],
+        }
+    }
+}
+
+impl From<Linear> for Polynomial {
+    fn from(linear: Linear) -> Self {
+        let mut terms: Vec<Monomial> = linear
+            .terms
+            .into_iter()
+            .map(|term| Monomial {
+                ids: vec![term.id],
+                coefficient: term.coefficient,
+            })
+            .collect();
+        terms.push(Monomial {
+            ids: Vec::new(),
+            coefficient: linear.constant,
+        });
+        Self { terms }
+    }
+}
+
+impl From<Quadratic> for Polynomial {
+    fn from(q: Quadratic) -> Self {
+        let mut terms: Vec<Monomial> =
+            itertools::multizip((q.rows.into_iter(), q.columns.into_iter(), q.values))
+                .map(|(row, column, coefficient)| Monomial {
+                    ids: vec![row, column],
+                    coefficient,
+                })
+                .collect();
+        if let Some(linear) = q.linear {
+            terms.extend(Polynomial::from(linear).terms);
+        }
+        Self { terms }
+    }
+}
+
+/// Fails if the polynomial has a term of degree three or higher
+impl TryFrom<Polynomial> for Quadratic {
+    type Error = Error;
+    fn try_from(poly: Polynomial) -> Result<Self> {
+        let mut quad = BTreeMap::new();
+        let mut linear = BTreeMap::new();
+        let mut constant = 0.0;
+        for (ids, coefficient) in poly.monomials() {
+            match ids.as_slice() {
+                [] => constant += coefficient,
+                [id] => *linear.entry(*id).or_insert(0.0) += coefficient,
+                [i, j] => *quad.entry((*i, *j)).or_insert(0.0) += coefficient,
+                _ => bail!("Polynomial has a term of degree {}: {:?}", ids.len(), ids),
+            }
+        }
+        Ok(Quadratic::from_terms(
+            quad,
+            Some(Linear::new(linear.into_iter(), constant)),
+        ))
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Self;
+
+    /// Monomials of the same IDs are merged, and the result is sorted by IDs.
+    fn add(self, rhs: Self) -> Self {
+        let mut terms = self.monomials();
+        for (ids, coefficient) in rhs.monomials() {
+            *terms.entry(ids).or_insert(0.0) += coefficient;
+        }
+        Self::from_terms(terms)
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+impl Sub for Polynomial {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Self;
+
+    /// Monomials of the same IDs are merged, and the result is sorted by IDs.
+    fn mul(self, rhs: Self) -> Self {
+        let rhs = rhs.monomials();
+        let mut terms = BTreeMap::new();
+        for (a, ca) in self.monomials() {
+            for (b, cb) in &rhs {
+                let mut ids: Vec<u64> = a.iter().chain(b).cloned().collect();
+                ids.sort_unstable();
+                *terms.entry(ids).or_insert(0.0) += ca * cb;
+            }
+        }
+        Self::from_terms(terms)
+    }
+}
+
+impl Mul<f64> for Polynomial {
+    type Output = Self;
+    fn mul(mut self, rhs: f64) -> Self {
+        for term in &mut self.terms {
+            term.coefficient *= rhs;
+        }
+        self
+    }
+}
@@ -0,0 +1,123 @@
+use crate::v1::{Linear, Quadratic};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+impl Quadratic {
+    pub fn used_decision_variable_ids(&self) -> BTreeSet<u64> {
+        self.columns
+            .iter()
+            .chain(self.rows.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Degree of the function, `2` if any quadratic term is stored. This is `O(1)`.
+    pub fn degree(&self) -> usize {
+        if self.values.is_empty() {
+            self.linear.as_ref().map_or(0, |linear| linear.degree())
+        } else {
+            2
+        }
+    }
+
+    /// Number of stored terms including the linear part, see [`Linear::num_terms`]. This is `O(1)`.
+    pub fn num_terms(&self) -> usize {
+        self.values.len() + self.linear.as_ref().map_or(0, |linear| linear.num_terms())
+    }
+
+    /// Create from quadratic terms keyed by `(row, column)` and an optional linear part
+    pub fn from_terms(terms: BTreeMap<(u64, u64), f64>, linear: Option<Linear>) -> Self {
+        let mut out = Self {
+            linear,
+            ..Default::default()
+        };
+        for ((row, column), value) in terms {
+            out.rows.push(row);
+            out.columns.push(column);
+            out.values.push(value);
+        }
+        out
+    }
+
+    /// Quadratic terms keyed by `(row, column)`, where duplicated entries are merged
+    pub fn quadratic_terms(&self) -> BTreeMap<(u64, u64), f64> {
+        let mut terms = BTreeMap::new();
+        for (row, column, value) in
+            itertools::multizip((self.rows.iter(), self.columns.iter(), self.values.iter()))
+        {
+            *terms.entry((*row, *column)).or_insert(0.0) += value;
+        }
+        terms
+    }
+
+    /// Replace `x_i * x_i` by `x_i` for binary variables `x_i` of `binary_ids`, since `x^2 = x` for `x in {0, 1}`
+    pub fn simplify_binary_squares(&mut self, binary_ids: &BTreeSet<u64>) {
+        let mut squares = BTreeMap::new();
+        let mut terms = BTreeMap::new();
+        for ((row, column), value) in self.quadratic_terms() {
+            if row == column && binary_ids.contains(&row) {
+                squares.insert(row, value);
+            } else {
+                terms.insert((row, column), value);
+            }
+        }
+        if squares.is_empty() {
+            return;
+        }
+        let linear = self.linear.take().unwrap_or_default() + Linear::new(squares.into_iter(), 0.0);
+        *self = Self::from_terms(terms, Some(linear));
+    }
+}
+
+impl From<Linear> for Quadratic {
+    fn from(linear: Linear) -> Self {
+        Self {
+            linear: Some(linear),
+            ..Default::default()
+        }
+    }
+}
+
+impl Add for Quadratic {
+    type Output = Self;
+
+    /// Entries of the same `(row, column)` are merged, and the result is sorted by row, then column.
+    fn add(self, rhs: Self) -> Self {
+        let mut terms = self.quadratic_terms();
+        for (key, value) in rhs.quadratic_terms() {
+            *terms.entry(key).or_insert(0.0) += value;
+        }
+        let linear = match (self.linear, rhs.linear) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        Self::from_terms(terms, linear)
+    }
+}
+
+impl Neg for Quadratic {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+impl Sub for Quadratic {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul<f64> for Quadratic {
+    type Output = Self;
+    fn mul(mut self, rhs: f64) -> Self {
+        for value in &mut self.values {
+            *value *= rhs;
+        }
+        self.linear = self.linear.map(|linear| linear * rhs);
+        self
+    }
+}
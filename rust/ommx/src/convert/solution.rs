@@ -0,0 +1,146 @@
+use crate::{
+    evaluate::FEASIBILITY_ATOL,
+    v1::{Bound, Instance, Solution},
+    Evaluate,
+};
+use anyhow::{ensure, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Relative tolerance for comparing objective values in [`Solution::to_fixed_instance`]
+const OBJECTIVE_RTOL: f64 = 1e-9;
+
+impl Solution {
+    /// Violation of each constraint, see [`EvaluatedConstraint::required_atol_for_feasibility`](crate::v1::EvaluatedConstraint::required_atol_for_feasibility)
+    pub fn constraint_violations(&self) -> Result<BTreeMap<u64, f64>> {
+        self.evaluated_constraints
+            .iter()
+            .map(|c| Ok((c.id, c.required_atol_for_feasibility()?)))
+            .collect()
+    }
+
+    /// IDs of constraints violated more than the tolerance used to determine [`Solution::feasible`]
+    pub fn infeasible_constraints(&self) -> Result<Vec<u64>> {
+        Ok(self
+            .constraint_violations()?
+            .into_iter()
+            .filter_map(|(id, violation)| (violation > FEASIBILITY_ATOL).then_some(id))
+            .collect())
+    }
+
+    /// Create an instance from `original` where every decision variable is fixed to its value in this solution
+    ///
+    /// The bound of each decision variable is replaced by `[value, value]`, and other parts of `original` are kept as is.
+    /// This fails if the solution does not consist of the decision variables of `original`, its state lacks some of them,
+    /// or its objective value differs from the one evaluated on `original` by more than `1e-6 + 1e-9 * max(|a|, |b|)`,
+    /// i.e. the objective values are compared relatively when they are large. The result is checked by [`Instance::verify_invariants`],
+    /// so a fractional value for an integer decision variable, e.g. of a relaxed solution, is also rejected.
+    ///
+    /// ```rust
+    /// use ommx::{Evaluate, v1::{decision_variable::Kind, instance::Sense, DecisionVariable, Instance, Linear, State}};
+    /// use std::collections::HashMap;
+    ///
+    /// let instance = Instance {
+    ///     decision_variables: (0..2)
+    ///         .map(|id| DecisionVariable { id, kind: Kind::Binary as i32, ..Default::default() })
+    ///         .collect(),
+    ///     objective: Some(Linear::new([(0, 1.0), (1, 2.0)].into_iter(), 0.0).into()),
+    ///     sense: Sense::Maximize as i32,
+    ///     ..Default::default()
+    /// };
+    /// let state: State = HashMap::from([(0, 1.0), (1, 0.0)]).into();
+    /// let (solution, _) = instance.evaluate(&state).unwrap();
+    ///
+    /// let fixed = solution.to_fixed_instance(&instance).unwrap();
+    /// assert_eq!(fixed.decision_variables[1].bound.as_ref().unwrap().upper, 0.0);
+    /// let (evaluated, _) = fixed.evaluate(&state).unwrap();
+    /// assert_eq!(evaluated.objective, solution.objective);
+    /// ```
+    pub fn to_fixed_instance(&self, original: &Instance) -> Result<Instance> {
+        let state = self
+            .state
+            .as_ref()
+            .context("State of the solution is not set")?;
+        let solution_ids: BTreeSet<u64> = self.decision_variables.iter().map(|v| v.id).collect();
+        let original_ids: BTreeSet<u64> =
+            original.decision_variables.iter().map(|v| v.id).collect();
+        ensure!(
+            solution_ids == original_ids,
+            "Decision variables of the solution do not match those of the instance"
+        );
+        let (evaluated, _) = original.evaluate(state)?;
+        let tolerance =
+            FEASIBILITY_ATOL + OBJECTIVE_RTOL * evaluated.objective.abs().max(self.objective.abs());
+        ensure!(
+            (evaluated.objective - self.objective).abs() <= tolerance,
+            "Objective value of the solution ({}) differs from the one evaluated on the instance ({})",
+            self.objective,
+            evaluated.objective
+        );
+
+        let mut instance = original.clone();
+        for v in &mut instance.decision_variables {
+            let value = *state.entries.get(&v.id).with_context(|| {
+                format!(
+                    "Value of decision variable ({}) is not found in the solution",
+                    v.id
+                )
+            })?;
+            v.bound = Some(Bound {
+                lower: value,
+                upper: value,
+            });
+        }
+        instance
+            .verify_invariants()
+            .context("Fixed instance is not valid")?;
+        Ok(instance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{decision_variable::Kind, instance::Sense, DecisionVariable, Linear, State};
+    use std::collections::HashMap;
+
+    fn instance(scale: f64) -> Instance {
+        Instance {
+            decision_variables: (0..2)
+                .map(|id| DecisionVariable {
+                    id,
+                    kind: Kind::Integer as i32,
+                    bound: Some(Bound {
+                        lower: 0.0,
+                        upper: 10.0,
+                    }),
+                    ..Default::default()
+                })
+                .collect(),
+            objective: Some(Linear::new([(0, scale), (1, 3.0 * scale)].into_iter(), 0.0).into()),
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn objective_tolerance() {
+        let state: State = HashMap::from([(0, 2.0), (1, 7.0)]).into();
+
+        // Large objective: an absolute difference above 1e-6 is accepted if it is relatively small
+        let large = instance(1e10);
+        let (mut solution, _) = large.evaluate(&state).unwrap();
+        assert_eq!(solution.objective, 2.3e11);
+        solution.objective += 1e-3;
+        assert!(solution.to_fixed_instance(&large).is_ok());
+        solution.objective += 1e3;
+        assert!(solution.to_fixed_instance(&large).is_err());
+
+        // Small objective: the absolute tolerance is used
+        let small = instance(1.0);
+        let (mut solution, _) = small.evaluate(&state).unwrap();
+        solution.objective += 1e-7;
+        assert!(solution.to_fixed_instance(&small).is_ok());
+        solution.objective += 1e-3;
+        assert!(solution.to_fixed_instance(&small).is_err());
+    }
+}
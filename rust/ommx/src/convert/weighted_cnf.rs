@@ -0,0 +1,402 @@
+use crate::v1::{decision_variable::Kind, instance::Sense, Equality, Instance, Polynomial};
+use anyhow::{bail, ensure, Context, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+
+/// Weighted CNF for MaxSAT solvers, created by [`Instance::to_weighted_cnf`]
+///
+/// Boolean variables are numbered from one as in the DIMACS format, and a literal is a variable number negated if it is false.
+///
+/// ```rust
+/// use ommx::v1::{instance::Sense, Constraint, DecisionVariable, Equality, Instance, Linear, decision_variable::Kind};
+///
+/// // Minimize `3 x0 + 2 x1 - 5 x2` subject to `x0 + x1 + x2 = 1`
+/// let instance = Instance {
+///     decision_variables: (0..3)
+///         .map(|id| DecisionVariable { id, kind: Kind::Binary as i32, ..Default::default() })
+///         .collect(),
+///     objective: Some(Linear::new([(0, 3.0), (1, 2.0), (2, -5.0)].into_iter(), 0.0).into()),
+///     constraints: vec![Constraint {
+///         id: 0,
+///         equality: Equality::EqualToZero as i32,
+///         function: Some(Linear::new([(0, 1.0), (1, 1.0), (2, 1.0)].into_iter(), -1.0).into()),
+///         ..Default::default()
+///     }],
+///     sense: Sense::Minimize as i32,
+///     ..Default::default()
+/// };
+/// let cnf = instance.to_weighted_cnf().unwrap();
+/// assert_eq!(cnf.variables[&2], 3);
+/// // `x0` and `x1` are penalized if true, and `x2` if false with the offset `-5`
+/// assert_eq!(cnf.soft_clauses, vec![(3, vec![-1]), (2, vec![-2]), (5, vec![3])]);
+/// assert_eq!(cnf.offset, -5.0);
+///
+/// let mut out = Vec::new();
+/// cnf.write_dimacs(&mut out).unwrap();
+/// let out = String::from_utf8(out).unwrap();
+/// assert!(out.starts_with(&format!("p wcnf {} {} 11\n", cnf.num_variables, cnf.hard_clauses.len() + 3)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedCnf {
+    /// Number of boolean variables including auxiliary ones introduced by the encoding of constraints
+    pub num_variables: u64,
+    /// Boolean variable of each binary decision variable, keyed by its ID
+    pub variables: BTreeMap<u64, u64>,
+    pub hard_clauses: Vec<Vec<i64>>,
+    /// Soft clauses with their positive weights
+    pub soft_clauses: Vec<(u64, Vec<i64>)>,
+    /// The objective value is this offset plus the total weight of falsified soft clauses for minimization,
+    /// and its negation for maximization
+    pub offset: f64,
+}
+
+impl WeightedCnf {
+    /// Write in the DIMACS WCNF format, where hard clauses have the weight `top`, one plus the total weight of soft clauses
+    ///
+    /// This fails if `top` overflows `u64`.
+    pub fn write_dimacs(&self, mut writer: impl Write) -> Result<()> {
+        let top = self
+            .soft_clauses
+            .iter()
+            .try_fold(1_u64, |top, (w, _)| top.checked_add(*w))
+            .context("Total weight of soft clauses overflows")?;
+        writeln!(
+            writer,
+            "p wcnf {} {} {top}",
+            self.num_variables,
+            self.hard_clauses.len() + self.soft_clauses.len()
+        )?;
+        let clauses = self
+            .hard_clauses
+            .iter()
+            .map(|clause| (top, clause))
+            .chain(self.soft_clauses.iter().map(|(w, clause)| (*w, clause)));
+        for (weight, clause) in clauses {
+            write!(writer, "{weight}")?;
+            for literal in clause {
+                write!(writer, " {literal}")?;
+            }
+            writeln!(writer, " 0")?;
+        }
+        Ok(())
+    }
+}
+
+impl Instance {
+    /// Convert into a [`WeightedCnf`] for MaxSAT solvers
+    ///
+    /// - All decision variables must be binary, and the objective and constraints must be linear with integer coefficients.
+    ///   The coefficients must be at most `2^53` in magnitude so that they are exactly represented,
+    ///   and this fails if the encoding of a constraint overflows `i64`.
+    ///   The constant terms of constraints must also be integers, while that of the objective is added to [`WeightedCnf::offset`].
+    /// - Constraints are encoded into hard clauses through binary decision diagrams of pseudo-Boolean constraints,
+    ///   which introduce auxiliary boolean variables. Bounds of binary decision variables fixing them are encoded as unit clauses.
+    /// - Each term `c x` of the objective to be minimized becomes a soft clause `!x` with the weight `c` if `c > 0`,
+    ///   or `x` with the weight `-c` and the offset `c` if `c < 0`. Maximization problems are negated.
+    pub fn to_weighted_cnf(&self) -> Result<WeightedCnf> {
+        let sign = match self.sense() {
+            Sense::Minimize => 1.0,
+            Sense::Maximize => -1.0,
+            Sense::Unspecified => bail!("Sense is not specified"),
+        };
+        let mut decision_variables: Vec<_> = self.decision_variables.iter().collect();
+        decision_variables.sort_by_key(|v| v.id);
+        let mut encoder = Encoder::default();
+        let mut variables = BTreeMap::new();
+        for v in decision_variables {
+            ensure!(
+                v.kind() == Kind::Binary,
+                "Weighted CNF supports only binary decision variables, but ({}) is {:?}",
+                v.id,
+                v.kind()
+            );
+            let x = encoder.new_variable();
+            variables.insert(v.id, x);
+            let bound = v.effective_bound();
+            if bound.lower > 0.0 {
+                encoder.hard_clauses.push(vec![x as i64]);
+            }
+            if bound.upper < 1.0 {
+                encoder.hard_clauses.push(vec![-(x as i64)]);
+            }
+        }
+
+        for c in &self.constraints {
+            let function = c
+                .function
+                .as_ref()
+                .with_context(|| format!("Function of constraint ({}) is not set", c.id))?;
+            let (terms, constant) = integer_linear(function.clone().into(), &variables)
+                .with_context(|| format!("Constraint ({}) cannot be encoded", c.id))?;
+            // `sum a_i x_i + b <= 0` is `sum a_i x_i <= -b`, and `= 0` is also `sum (-a_i) x_i <= b`
+            encoder
+                .encode_at_most(&terms, -constant)
+                .with_context(|| format!("Constraint ({}) cannot be encoded", c.id))?;
+            match c.equality() {
+                Equality::LessThanOrEqualToZero => {}
+                Equality::EqualToZero => {
+                    let negated: Vec<_> = terms.iter().map(|(x, a)| (*x, -a)).collect();
+                    encoder
+                        .encode_at_most(&negated, constant)
+                        .with_context(|| format!("Constraint ({}) cannot be encoded", c.id))?;
+                }
+                Equality::Unspecified => {
+                    bail!("Equality of constraint ({}) is not specified", c.id)
+                }
+            }
+        }
+
+        let mut soft_clauses = Vec::new();
+        let mut offset = 0.0;
+        if let Some(objective) = &self.objective {
+            for (ids, coefficient) in Polynomial::from(objective.clone()).monomials() {
+                let coefficient = sign * coefficient;
+                match ids.as_slice() {
+                    [] => offset += coefficient,
+                    [id] if coefficient != 0.0 => {
+                        let weight = exact_integer(coefficient).with_context(|| {
+                            format!("Coefficient of ({id}) in the objective cannot be encoded")
+                        })?;
+                        let x = *variables.get(id).with_context(|| {
+                            format!("Decision variable ({id}) is used but not defined")
+                        })? as i64;
+                        if weight > 0 {
+                            soft_clauses.push((weight.unsigned_abs(), vec![-x]));
+                        } else {
+                            soft_clauses.push((weight.unsigned_abs(), vec![x]));
+                            offset += coefficient;
+                        }
+                    }
+                    [_] => {}
+                    _ => bail!(
+                        "Weighted CNF requires a linear objective, but it has a term of {ids:?}"
+                    ),
+                }
+            }
+        }
+
+        Ok(WeightedCnf {
+            num_variables: encoder.num_variables,
+            variables,
+            hard_clauses: encoder.hard_clauses,
+            soft_clauses,
+            offset,
+        })
+    }
+}
+
+/// Integer terms `(boolean variable, coefficient)` and the integer constant of a linear function
+fn integer_linear(
+    poly: Polynomial,
+    variables: &BTreeMap<u64, u64>,
+) -> Result<(Vec<(u64, i64)>, i64)> {
+    let mut terms = Vec::new();
+    let mut constant = 0;
+    for (ids, coefficient) in poly.monomials() {
+        let coefficient = exact_integer(coefficient)
+            .with_context(|| format!("Coefficient of {ids:?} cannot be encoded"))?;
+        match ids.as_slice() {
+            [] => constant = coefficient,
+            [id] => terms.push((
+                *variables
+                    .get(id)
+                    .with_context(|| format!("Decision variable ({id}) is used but not defined"))?,
+                coefficient,
+            )),
+            _ => bail!("Weighted CNF requires linear constraints, but a term of {ids:?} is given"),
+        }
+    }
+    Ok((terms, constant))
+}
+
+/// Largest magnitude of integers exactly represented by `f64`
+const MAX_EXACT_INTEGER: f64 = (1_u64 << 53) as f64;
+
+/// Convert an integer coefficient into `i64`, rejecting fractional values and those not exactly represented by `f64`
+fn exact_integer(value: f64) -> Result<i64> {
+    ensure!(
+        value.fract() == 0.0,
+        "Weighted CNF requires integer coefficients, but {value} is given"
+    );
+    ensure!(
+        value.abs() <= MAX_EXACT_INTEGER,
+        "Coefficient {value} exceeds 2^53 in magnitude"
+    );
+    Ok(value as i64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    True,
+    False,
+    Variable(i64),
+}
+
+#[derive(Default)]
+struct Encoder {
+    num_variables: u64,
+    hard_clauses: Vec<Vec<i64>>,
+}
+
+impl Encoder {
+    fn new_variable(&mut self) -> u64 {
+        self.num_variables += 1;
+        self.num_variables
+    }
+
+    /// Encode `sum a_i x_i <= k` into hard clauses, failing if the sums of coefficients overflow
+    fn encode_at_most(&mut self, terms: &[(u64, i64)], mut k: i64) -> Result<()> {
+        // `a x` with `a < 0` is `a + |a| !x`, so that all coefficients are positive
+        let mut literals = Vec::new();
+        for &(x, a) in terms {
+            match a {
+                0 => {}
+                a if a > 0 => literals.push((x as i64, a)),
+                a => {
+                    literals.push((-(x as i64), -a));
+                    k = k.checked_sub(a).context("Right-hand side overflows")?;
+                }
+            }
+        }
+        // Larger coefficients first for smaller diagrams
+        literals.sort_by_key(|(_, a)| -a);
+        let mut suffix_sums = vec![0_i64; literals.len() + 1];
+        for i in (0..literals.len()).rev() {
+            suffix_sums[i] = suffix_sums[i + 1]
+                .checked_add(literals[i].1)
+                .context("Sum of coefficients overflows")?;
+        }
+        match self.node(&literals, &suffix_sums, 0, k, &mut HashMap::new()) {
+            Node::True => {}
+            Node::False => {
+                // Trivially infeasible
+                let x = self.new_variable() as i64;
+                self.hard_clauses.push(vec![x]);
+                self.hard_clauses.push(vec![-x]);
+            }
+            Node::Variable(root) => self.hard_clauses.push(vec![root]),
+        }
+        Ok(())
+    }
+
+    /// Node of the diagram representing `sum_{j >= i} a_j l_j <= k`, where the node variable implies it
+    fn node(
+        &mut self,
+        literals: &[(i64, i64)],
+        suffix_sums: &[i64],
+        i: usize,
+        k: i64,
+        memo: &mut HashMap<(usize, i64), Node>,
+    ) -> Node {
+        if k < 0 {
+            return Node::False;
+        }
+        if suffix_sums[i] <= k {
+            return Node::True;
+        }
+        if let Some(node) = memo.get(&(i, k)) {
+            return *node;
+        }
+        let (l, a) = literals[i];
+        let high = self.node(literals, suffix_sums, i + 1, k - a, memo);
+        let low = self.node(literals, suffix_sums, i + 1, k, memo);
+        let node = if high == low {
+            high
+        } else {
+            let v = self.new_variable() as i64;
+            // `v -> (l -> high)` and `v -> (!l -> low)`
+            for (literal, child) in [(l, high), (-l, low)] {
+                match child {
+                    Node::True => {}
+                    Node::False => self.hard_clauses.push(vec![-v, -literal]),
+                    Node::Variable(c) => self.hard_clauses.push(vec![-v, -literal, c]),
+                }
+            }
+            Node::Variable(v)
+        };
+        memo.insert((i, k), node);
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{Constraint, DecisionVariable, Linear};
+
+    fn binary_instance(n: u64, objective: Linear, constraints: Vec<Linear>) -> Instance {
+        Instance {
+            decision_variables: (0..n)
+                .map(|id| DecisionVariable {
+                    id,
+                    kind: Kind::Binary as i32,
+                    ..Default::default()
+                })
+                .collect(),
+            objective: Some(objective.into()),
+            constraints: constraints
+                .into_iter()
+                .zip(0..)
+                .map(|(f, id)| Constraint {
+                    id,
+                    equality: Equality::LessThanOrEqualToZero as i32,
+                    function: Some(f.into()),
+                    ..Default::default()
+                })
+                .collect(),
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reject_inexact_coefficients() {
+        let huge = 2.0_f64.powi(60);
+        let instance = binary_instance(1, Linear::new([(0, huge)].into_iter(), 0.0), vec![]);
+        assert!(instance.to_weighted_cnf().is_err());
+        let instance = binary_instance(
+            1,
+            Linear::new(std::iter::empty(), 0.0),
+            vec![Linear::new([(0, 1.0)].into_iter(), -huge)],
+        );
+        assert!(instance.to_weighted_cnf().is_err());
+        // `2^53` itself is exact
+        let instance = binary_instance(
+            1,
+            Linear::new([(0, MAX_EXACT_INTEGER)].into_iter(), 0.0),
+            vec![],
+        );
+        assert!(instance.to_weighted_cnf().is_ok());
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        // Sum of 1024 coefficients of `2^53` is `2^63`, which overflows `i64`
+        let n = 1024;
+        let terms = (0..n).map(|id| (id, MAX_EXACT_INTEGER));
+        let instance = binary_instance(
+            n,
+            Linear::new(std::iter::empty(), 0.0),
+            vec![Linear::new(terms.clone(), -1.0)],
+        );
+        assert!(instance.to_weighted_cnf().is_err());
+        // Negative coefficients move to the right-hand side
+        let instance = binary_instance(
+            n,
+            Linear::new(std::iter::empty(), 0.0),
+            vec![Linear::new(terms.map(|(id, a)| (id, -a)), 0.0)],
+        );
+        assert!(instance.to_weighted_cnf().is_err());
+
+        let cnf = WeightedCnf {
+            num_variables: 2,
+            variables: BTreeMap::new(),
+            hard_clauses: vec![vec![1, 2]],
+            soft_clauses: vec![(u64::MAX / 2, vec![1]), (u64::MAX / 2 + 1, vec![2])],
+            offset: 0.0,
+        };
+        assert!(cnf.write_dimacs(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! Field-level delta between two serialized [`v1::Instance`] messages
+//!
+//! The delta records decision variables and constraints added, removed, or modified by comparing them by their IDs,
+//! and replaces the description, objective, and sense only when they differ.
+//! This is much smaller than the full instance for a family of related instances.
+//!
+//! ```rust
+//! use ommx::{diff, random::random_lp, Message};
+//! use rand::{rngs::StdRng, SeedableRng};
+//!
+//! let base = random_lp(&mut StdRng::seed_from_u64(0), 5, 7);
+//! let mut new = base.clone();
+//! new.constraints.pop();
+//!
+//! let delta = diff::encode_delta(&base.encode_to_vec(), &new.encode_to_vec()).unwrap();
+//! let applied = diff::apply_delta(&base.encode_to_vec(), &delta).unwrap();
+//! assert_eq!(ommx::v1::Instance::decode(applied.as_slice()).unwrap(), new);
+//! ```
+
+use crate::v1::{self, instance::Description, Constraint, DecisionVariable, Function};
+use anyhow::{ensure, Context, Result};
+use prost::Message;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Delta between two instances. This is encoded as a protobuf message, but not a part of the OMMX Messages.
+#[derive(Clone, PartialEq, Message)]
+struct InstanceDelta {
+    /// Replace the description if `replace_description` is true
+    #[prost(message, optional, tag = "1")]
+    description: Option<Description>,
+    #[prost(bool, tag = "2")]
+    replace_description: bool,
+
+    /// Replace the objective if `replace_objective` is true
+    #[prost(message, optional, tag = "3")]
+    objective: Option<Function>,
+    #[prost(bool, tag = "4")]
+    replace_objective: bool,
+
+    #[prost(enumeration = "v1::instance::Sense", tag = "5")]
+    sense: i32,
+
+    /// Decision variables added or modified
+    #[prost(message, repeated, tag = "6")]
+    decision_variables: Vec<DecisionVariable>,
+    #[prost(uint64, repeated, tag = "7")]
+    removed_decision_variable_ids: Vec<u64>,
+    /// Order of the decision variables, set only when it cannot be reproduced from the base
+    #[prost(uint64, repeated, tag = "8")]
+    decision_variable_order: Vec<u64>,
+
+    /// Constraints added or modified
+    #[prost(message, repeated, tag = "9")]
+    constraints: Vec<Constraint>,
+    #[prost(uint64, repeated, tag = "10")]
+    removed_constraint_ids: Vec<u64>,
+    /// Order of the constraints, set only when it cannot be reproduced from the base
+    #[prost(uint64, repeated, tag = "11")]
+    constraint_order: Vec<u64>,
+}
+
+/// Encode the delta from `base` to `new`, both are serialized [`v1::Instance`]
+pub fn encode_delta(base: &[u8], new: &[u8]) -> Result<Vec<u8>> {
+    let base = v1::Instance::decode(base)?;
+    let new = v1::Instance::decode(new)?;
+
+    let (decision_variables, removed_decision_variable_ids, decision_variable_order) =
+        diff_by_id(&base.decision_variables, &new.decision_variables, |v| v.id)?;
+    let (constraints, removed_constraint_ids, constraint_order) =
+        diff_by_id(&base.constraints, &new.constraints, |c| c.id)?;
+
+    let replace_description = base.description != new.description;
+    let replace_objective = base.objective != new.objective;
+    let delta = InstanceDelta {
+        description: new.description.filter(|_| replace_description),
+        replace_description,
+        objective: new.objective.filter(|_| replace_objective),
+        replace_objective,
+        sense: new.sense,
+        decision_variables,
+        removed_decision_variable_ids,
+        decision_variable_order,
+        constraints,
+        removed_constraint_ids,
+        constraint_order,
+    };
+    Ok(delta.encode_to_vec())
+}
+
+/// Apply the delta created by [`encode_delta`] to `base`, and returns the serialized [`v1::Instance`]
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let mut instance = v1::Instance::decode(base)?;
+    let delta = InstanceDelta::decode(delta)?;
+
+    if delta.replace_description {
+        instance.description = delta.description;
+    }
+    if delta.replace_objective {
+        instance.objective = delta.objective;
+    }
+    instance.sense = delta.sense;
+    instance.decision_variables = apply_by_id(
+        instance.decision_variables,
+        delta.decision_variables,
+        &delta.removed_decision_variable_ids,
+        &delta.decision_variable_order,
+        |v| v.id,
+    )?;
+    instance.constraints = apply_by_id(
+        instance.constraints,
+        delta.constraints,
+        &delta.removed_constraint_ids,
+        &delta.constraint_order,
+        |c| c.id,
+    )?;
+    Ok(instance.encode_to_vec())
+}
+
+fn ensure_unique_ids<T>(items: &[T], id: impl Fn(&T) -> u64) -> Result<()> {
+    let mut ids = BTreeSet::new();
+    for item in items {
+        ensure!(ids.insert(id(item)), "Duplicated ID: {}", id(item));
+    }
+    Ok(())
+}
+
+/// Returns items added or modified, IDs removed, and the order of IDs if it cannot be reproduced by [`apply_by_id`]
+fn diff_by_id<T: Clone + PartialEq>(
+    base: &[T],
+    new: &[T],
+    id: impl Fn(&T) -> u64,
+) -> Result<(Vec<T>, Vec<u64>, Vec<u64>)> {
+    ensure_unique_ids(base, &id)?;
+    ensure_unique_ids(new, &id)?;
+    let base_map: BTreeMap<u64, &T> = base.iter().map(|item| (id(item), item)).collect();
+    let new_ids: Vec<u64> = new.iter().map(&id).collect();
+    let new_id_set: BTreeSet<u64> = new_ids.iter().cloned().collect();
+
+    let upserts: Vec<T> = new
+        .iter()
+        .filter(|item| base_map.get(&id(item)) != Some(item))
+        .cloned()
+        .collect();
+    let removed: Vec<u64> = base
+        .iter()
+        .map(&id)
+        .filter(|i| !new_id_set.contains(i))
+        .collect();
+
+    // Order reproduced by `apply_by_id`: kept items in the base order, and then added items
+    let natural: Vec<u64> = base
+        .iter()
+        .map(&id)
+        .filter(|i| new_id_set.contains(i))
+        .chain(
+            upserts
+                .iter()
+                .map(&id)
+                .filter(|i| !base_map.contains_key(i)),
+        )
+        .collect();
+    let order = if natural == new_ids {
+        Vec::new()
+    } else {
+        new_ids
+    };
+    Ok((upserts, removed, order))
+}
+
+fn apply_by_id<T>(
+    base: Vec<T>,
+    upserts: Vec<T>,
+    removed: &[u64],
+    order: &[u64],
+    id: impl Fn(&T) -> u64,
+) -> Result<Vec<T>> {
+    let removed: BTreeSet<u64> = removed.iter().cloned().collect();
+    let base_ids: BTreeSet<u64> = base.iter().map(&id).collect();
+    let (modified, added): (Vec<T>, Vec<T>) = upserts
+        .into_iter()
+        .partition(|item| base_ids.contains(&id(item)));
+    let mut modified: BTreeMap<u64, T> =
+        modified.into_iter().map(|item| (id(&item), item)).collect();
+
+    let mut out: Vec<T> = base
+        .into_iter()
+        .filter(|item| !removed.contains(&id(item)))
+        .map(|item| modified.remove(&id(&item)).unwrap_or(item))
+        .collect();
+    out.extend(added);
+    if order.is_empty() {
+        return Ok(out);
+    }
+
+    ensure!(
+        out.len() == order.len(),
+        "Order has {} IDs, but {} items exist",
+        order.len(),
+        out.len()
+    );
+    let mut items: BTreeMap<u64, T> = out.into_iter().map(|item| (id(&item), item)).collect();
+    order
+        .iter()
+        .map(|i| {
+            items
+                .remove(i)
+                .with_context(|| format!("ID {i} in the order is not found"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{random::random_lp, v1::Linear};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn base() -> v1::Instance {
+        let mut instance = random_lp(&mut StdRng::seed_from_u64(0), 5, 7);
+        instance.decision_variables = (0..5)
+            .map(|id| DecisionVariable {
+                id,
+                kind: v1::decision_variable::Kind::Continuous as i32,
+                ..Default::default()
+            })
+            .collect();
+        instance
+    }
+
+    /// Apply the delta from `base` to `new`, and returns the delta
+    fn round_trip(base: &v1::Instance, new: &v1::Instance) -> InstanceDelta {
+        let delta = encode_delta(&base.encode_to_vec(), &new.encode_to_vec()).unwrap();
+        let applied = apply_delta(&base.encode_to_vec(), &delta).unwrap();
+        assert_eq!(&v1::Instance::decode(applied.as_slice()).unwrap(), new);
+        InstanceDelta::decode(delta.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        let base = base();
+        let delta = round_trip(&base, &base);
+        assert_eq!(
+            delta,
+            InstanceDelta {
+                sense: base.sense,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn reorder() {
+        let base = base();
+        let mut new = base.clone();
+        new.constraints.reverse();
+        new.decision_variables.rotate_left(1);
+        let delta = round_trip(&base, &new);
+        assert!(delta.constraints.is_empty());
+        assert!(delta.decision_variables.is_empty());
+        assert_eq!(
+            delta.constraint_order,
+            new.constraints.iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            delta.decision_variable_order,
+            new.decision_variables
+                .iter()
+                .map(|v| v.id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn modify() {
+        let base = base();
+        let mut new = base.clone();
+        new.constraints[2].function = Some(Linear::new([(0, 1.0)].into_iter(), -1.0).into());
+        new.objective = Some(Linear::new([(1, 1.0)].into_iter(), 0.0).into());
+        let delta = round_trip(&base, &new);
+        assert_eq!(delta.constraints, vec![new.constraints[2].clone()]);
+        assert!(delta.removed_constraint_ids.is_empty());
+        assert!(delta.constraint_order.is_empty());
+        assert!(delta.replace_objective);
+        assert!(!delta.replace_description);
+    }
+
+    #[test]
+    fn remove_and_add() {
+        let base = base();
+        let mut new = base.clone();
+        let removed = new.constraints.remove(3);
+        let mut added = new.constraints[0].clone();
+        added.id = 100;
+        new.constraints.push(added.clone());
+        let delta = round_trip(&base, &new);
+        assert_eq!(delta.removed_constraint_ids, vec![removed.id]);
+        assert_eq!(delta.constraints, vec![added]);
+        // Kept constraints in the base order followed by added ones, and thus the order is not recorded
+        assert!(delta.constraint_order.is_empty());
+    }
+
+    #[test]
+    fn duplicated_ids() {
+        let base = base();
+        let mut new = base.clone();
+        new.constraints.push(new.constraints[0].clone());
+        assert!(encode_delta(&base.encode_to_vec(), &new.encode_to_vec()).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 use crate::v1::{
-    function::Function as FunctionEnum, linear::Term as LinearTerm, Constraint, Equality,
-    EvaluatedConstraint, Function, Instance, Linear, Optimality, Polynomial, Quadratic, Relaxation,
-    Solution, State,
+    function::Function as FunctionEnum, instance::Sense, linear::Term as LinearTerm, Bound,
+    Constraint, DecisionVariable, EvaluatedConstraint, Function, Instance, Linear, Optimality,
+    Polynomial, Quadratic, Relaxation, Solution, State,
 };
-use anyhow::{bail, Context, Result};
-use std::collections::BTreeSet;
+use anyhow::{bail, ensure, Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// FIXME: Add a way to specify the tolerance
+/// Absolute tolerance used to determine the feasibility of evaluated constraints
+pub(crate) const FEASIBILITY_ATOL: f64 = 1e-6;
 
 /// Evaluate with a [State]
 pub trait Evaluate {
@@ -121,6 +125,9 @@ impl Evaluate for Constraint {
 impl Evaluate for Instance {
     type Output = Solution;
 
+    /// Decision variables of the solution are copied from the instance,
+    /// and thus their metadata like names, subscripts, parameters, and descriptions are preserved.
+    /// Solver adapters creating a [`Solution`] by this method keep this guarantee.
     fn evaluate(&self, state: &State) -> Result<(Self::Output, BTreeSet<u64>)> {
         let mut used_ids = BTreeSet::new();
         let mut evaluated_constraints = Vec::new();
@@ -128,17 +135,8 @@ impl Evaluate for Instance {
         for c in &self.constraints {
             let (c, used_ids_) = c.evaluate(state)?;
             used_ids.extend(used_ids_);
-            if c.equality == Equality::EqualToZero as i32 {
-                // FIXME: Add a way to specify the tolerance
-                if c.evaluated_value.abs() > 1e-6 {
-                    feasible = false;
-                }
-            } else if c.equality == Equality::LessThanOrEqualToZero as i32 {
-                if c.evaluated_value > 1e-6 {
-                    feasible = false;
-                }
-            } else {
-                bail!("Unsupported equality: {:?}", c.equality);
+            if c.required_atol_for_feasibility()? > FEASIBILITY_ATOL {
+                feasible = false;
             }
             evaluated_constraints.push(c);
         }
@@ -163,3 +161,463 @@ impl Evaluate for Instance {
         ))
     }
 }
+
+impl Instance {
+    /// Evaluate as [`Evaluate::evaluate`], and tag the solution with `relaxation`
+    ///
+    /// Use this when the state comes from a relaxed problem, e.g. [`Relaxation::LpRelaxed`] for LP relaxation.
+    /// [`Solution::set_relaxation`] can also be used to tag the solution afterward.
+    pub fn evaluate_with_relaxation(
+        &self,
+        state: &State,
+        relaxation: Relaxation,
+    ) -> Result<(Solution, BTreeSet<u64>)> {
+        let (mut solution, used_ids) = self.evaluate(state)?;
+        solution.set_relaxation(relaxation);
+        Ok((solution, used_ids))
+    }
+
+    /// Evaluate as [`Evaluate::evaluate`], and attach dual variables keyed by constraint IDs to evaluated constraints
+    ///
+    /// The feasibility of the solution is determined with `atol` instead of the default tolerance.
+    /// Constraints not in `duals` keep `dual_variable` unset, and this fails if `duals` contains an unknown constraint ID.
+    pub fn evaluate_with_duals(
+        &self,
+        state: &State,
+        duals: &BTreeMap<u64, f64>,
+        atol: f64,
+    ) -> Result<Solution> {
+        let ids: BTreeSet<u64> = self.constraints.iter().map(|c| c.id).collect();
+        for id in duals.keys() {
+            ensure!(
+                ids.contains(id),
+                "Dual variable is given for unknown constraint ({id})"
+            );
+        }
+        let (mut solution, _) = self.evaluate(state)?;
+        let mut feasible = true;
+        for c in &mut solution.evaluated_constraints {
+            c.dual_variable = duals.get(&c.id).cloned();
+            if c.required_atol_for_feasibility()? > atol {
+                feasible = false;
+            }
+        }
+        solution.feasible = feasible;
+        Ok(solution)
+    }
+
+    /// Weighted sum of violations of constraints at `state`, see [`Solution::constraint_violations`]
+    ///
+    /// Violations within `atol` are regarded as zero. Constraints not in `weights` are weighted by one,
+    /// and this fails if `weights` contains an unknown constraint ID or a negative weight.
+    pub fn weighted_violation(
+        &self,
+        state: &State,
+        weights: &BTreeMap<u64, f64>,
+        atol: f64,
+    ) -> Result<f64> {
+        let ids: BTreeSet<u64> = self.constraints.iter().map(|c| c.id).collect();
+        for (id, weight) in weights {
+            ensure!(
+                ids.contains(id),
+                "Weight is given for unknown constraint ({id})"
+            );
+            ensure!(
+                *weight >= 0.0,
+                "Weight of constraint ({id}) is negative: {weight}"
+            );
+        }
+        let mut total = 0.0;
+        for c in &self.constraints {
+            let (evaluated, _) = c.evaluate(state)?;
+            let violation = evaluated.required_atol_for_feasibility()?;
+            if violation > atol {
+                total += weights.get(&c.id).unwrap_or(&1.0) * violation;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Estimate the ratio of the feasible region in the box of decision variables by `n` random states of [`random_state`](crate::random::random_state)
+    ///
+    /// The feasibility of each state is determined with `atol`. Since constraints of equality have measure zero for continuous variables,
+    /// this is meaningful mainly for problems of inequality constraints or discrete variables.
+    pub fn feasibility_ratio_estimate(
+        &self,
+        n: usize,
+        rng: &mut impl rand::Rng,
+        atol: f64,
+    ) -> Result<f64> {
+        ensure!(n > 0, "Number of samples must be positive");
+        let mut feasible = 0;
+        for _ in 0..n {
+            let state = crate::random::random_state(rng, &self.decision_variables)?;
+            if self.is_feasible(&state, atol)? {
+                feasible += 1;
+            }
+        }
+        Ok(feasible as f64 / n as f64)
+    }
+
+    /// Evaluate samples one by one, and call `callback` with the sample ID, the objective value, and the feasibility
+    ///
+    /// Unlike [`Evaluate::evaluate`], this does not create [`Solution`]s, and thus the memory usage does not grow with the number of samples.
+    /// The feasibility is determined with `atol`. This stops at the first sample which cannot be evaluated.
+    pub fn evaluate_samples_streaming(
+        &self,
+        samples: impl IntoIterator<Item = (u64, State)>,
+        atol: f64,
+        mut callback: impl FnMut(u64, f64, bool),
+    ) -> Result<()> {
+        let objective = self.objective.as_ref().context("Objective is not set")?;
+        for (sample_id, state) in samples {
+            let (value, _) = objective
+                .evaluate(&state)
+                .with_context(|| format!("Failed to evaluate sample ({sample_id})"))?;
+            let feasible = self
+                .is_feasible(&state, atol)
+                .with_context(|| format!("Failed to evaluate sample ({sample_id})"))?;
+            callback(sample_id, value, feasible);
+        }
+        Ok(())
+    }
+
+    /// Enumerate all feasible states of a small instance of discrete decision variables
+    ///
+    /// Binary, integer, and semi-integer decision variables take the integer values in their bounds (and zero for semi-integer),
+    /// and states in the Cartesian product of them are filtered by the feasibility with `atol`.
+    /// This fails if there is a continuous or unbounded decision variable, or the product has more than `limit` states.
+    pub fn enumerate_feasible(&self, atol: f64, limit: usize) -> Result<Vec<State>> {
+        let domains = self
+            .decision_variables
+            .iter()
+            .map(|v| Ok((v.id, discrete_domain(v, limit)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let size = domains
+            .iter()
+            .try_fold(1_usize, |size, (_, domain)| size.checked_mul(domain.len()));
+        match size {
+            Some(size) if size <= limit => {}
+            _ => bail!("Number of states exceeds the limit {limit}"),
+        }
+        if domains.iter().any(|(_, domain)| domain.is_empty()) {
+            return Ok(Vec::new());
+        }
+
+        let mut feasible = Vec::new();
+        let mut digits = vec![0; domains.len()];
+        loop {
+            let state: State = domains
+                .iter()
+                .zip(&digits)
+                .map(|((id, domain), digit)| (*id, domain[*digit]))
+                .collect::<HashMap<u64, f64>>()
+                .into();
+            if self.is_feasible(&state, atol)? {
+                feasible.push(state);
+            }
+            // Increment digits as an odometer
+            let Some(n) = (0..digits.len()).find(|n| digits[*n] + 1 < domains[*n].1.len()) else {
+                break;
+            };
+            digits[n] += 1;
+            digits[..n].fill(0);
+        }
+        Ok(feasible)
+    }
+
+    /// Find an optimal state and its objective value by [`Instance::enumerate_feasible`], or `None` if the instance is infeasible
+    ///
+    /// This is intended as a ground truth for testing solvers on small instances.
+    /// The first one in the enumeration order is returned if there are multiple optimal states.
+    pub fn brute_force_optimum(&self, atol: f64, limit: usize) -> Result<Option<(State, f64)>> {
+        let sign = match self.sense() {
+            Sense::Minimize => 1.0,
+            Sense::Maximize => -1.0,
+            Sense::Unspecified => bail!("Sense is not specified"),
+        };
+        let objective = self.objective.as_ref().context("Objective is not set")?;
+        let mut best: Option<(State, f64)> = None;
+        for state in self.enumerate_feasible(atol, limit)? {
+            let (value, _) = objective.evaluate(&state)?;
+            let improved = match &best {
+                Some((_, best)) => sign * value < sign * best,
+                None => true,
+            };
+            if improved {
+                best = Some((state, value));
+            }
+        }
+        Ok(best)
+    }
+
+    /// Check if all constraints are satisfied within `atol`, stopping at the first violated one
+    fn is_feasible(&self, state: &State, atol: f64) -> Result<bool> {
+        for c in &self.constraints {
+            let (c, _) = c.evaluate(state)?;
+            if c.required_atol_for_feasibility()? > atol {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Values a discrete decision variable can take, used in [`Instance::enumerate_feasible`]
+///
+/// This fails if the number of values exceeds `limit` to avoid allocating a huge domain.
+fn discrete_domain(v: &DecisionVariable, limit: usize) -> Result<Vec<f64>> {
+    let kind = v.kind();
+    let (is_integer, can_be_zero) = kind.to_variable_flags()?;
+    ensure!(
+        is_integer,
+        "Decision variable ({}) is not discrete: {kind:?}",
+        v.id
+    );
+    let Bound { lower, upper } = v.effective_bound();
+    ensure!(
+        lower.is_finite() && upper.is_finite(),
+        "Decision variable ({}) is unbounded",
+        v.id
+    );
+    ensure!(
+        upper.floor() - lower.ceil() < limit as f64,
+        "Number of states exceeds the limit {limit}"
+    );
+    let mut domain: Vec<f64> = (lower.ceil() as i64..=upper.floor() as i64)
+        .map(|value| value as f64)
+        .collect();
+    if can_be_zero && !domain.contains(&0.0) {
+        domain.push(0.0);
+    }
+    Ok(domain)
+}
+
+/// [`Function`] compiled for evaluation with values given as a dense slice, created by [`Function::to_dense`]
+///
+/// Decision variables are resolved to positions in the ordering at compilation,
+/// so that each evaluation only indexes the slice. This is intended for numerical optimizers evaluating the same function many times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseFunction {
+    num_variables: usize,
+    constant: f64,
+    linear: Vec<(usize, f64)>,
+    quadratic: Vec<(usize, usize, f64)>,
+    higher: Vec<(Vec<usize>, f64)>,
+}
+
+impl DenseFunction {
+    /// Evaluate with `values`, where `values[i]` is the value of the `i`-th decision variable of the ordering
+    pub fn evaluate(&self, values: &[f64]) -> Result<f64> {
+        ensure!(
+            values.len() == self.num_variables,
+            "Length of values ({}) and ordering ({}) mismatch",
+            values.len(),
+            self.num_variables
+        );
+        let mut sum = self.constant;
+        for (i, coefficient) in &self.linear {
+            sum += coefficient * values[*i];
+        }
+        for (i, j, coefficient) in &self.quadratic {
+            sum += coefficient * values[*i] * values[*j];
+        }
+        for (positions, coefficient) in &self.higher {
+            sum += positions
+                .iter()
+                .fold(*coefficient, |product, i| product * values[*i]);
+        }
+        Ok(sum)
+    }
+}
+
+impl Function {
+    /// Compile into a [`DenseFunction`] evaluated with values aligned to `ordering`
+    ///
+    /// `ordering` can be any order of decision variable IDs without duplicates, and may contain IDs not used in the function.
+    /// Terms of the same monomial are merged, and those whose coefficients are within `atol` of zero are dropped.
+    /// This fails if the function uses a decision variable not in `ordering`.
+    ///
+    /// ```rust
+    /// use ommx::v1::{Function, Linear, Quadratic};
+    ///
+    /// // `2 x3 x1 + x1 + 1`
+    /// let f: Function = Quadratic {
+    ///     rows: vec![3],
+    ///     columns: vec![1],
+    ///     values: vec![2.0],
+    ///     linear: Some(Linear::new([(1, 1.0)].into_iter(), 1.0)),
+    /// }
+    /// .into();
+    /// let dense = f.to_dense(&[3, 1], 1e-12).unwrap();
+    /// assert_eq!(dense.evaluate(&[2.0, 5.0]).unwrap(), 2.0 * 2.0 * 5.0 + 5.0 + 1.0);
+    /// ```
+    pub fn to_dense(&self, ordering: &[u64], atol: f64) -> Result<DenseFunction> {
+        let mut position = HashMap::with_capacity(ordering.len());
+        for (i, id) in ordering.iter().enumerate() {
+            ensure!(
+                position.insert(*id, i).is_none(),
+                "Ordering has duplicated ID ({id})"
+            );
+        }
+        let mut dense = DenseFunction {
+            num_variables: ordering.len(),
+            constant: 0.0,
+            linear: Vec::new(),
+            quadratic: Vec::new(),
+            higher: Vec::new(),
+        };
+        // Monomials keyed by their sorted IDs, so that e.g. `x1 * x2` and `x2 * x1` are merged
+        let mut monomials = BTreeMap::new();
+        let mut add = |mut ids: Vec<u64>, coefficient: f64| {
+            ids.sort_unstable();
+            *monomials.entry(ids).or_insert(0.0) += coefficient;
+        };
+        let add_linear = |add: &mut dyn FnMut(Vec<u64>, f64), linear: &Linear| {
+            for LinearTerm { id, coefficient } in &linear.terms {
+                add(vec![*id], *coefficient);
+            }
+            add(Vec::new(), linear.constant);
+        };
+        match &self.function {
+            Some(FunctionEnum::Constant(c)) => add(Vec::new(), *c),
+            Some(FunctionEnum::Linear(linear)) => add_linear(&mut add, linear),
+            Some(FunctionEnum::Quadratic(quadratic)) => {
+                for (i, j, value) in itertools::multizip((
+                    quadratic.rows.iter(),
+                    quadratic.columns.iter(),
+                    quadratic.values.iter(),
+                )) {
+                    add(vec![*i, *j], *value);
+                }
+                if let Some(linear) = &quadratic.linear {
+                    add_linear(&mut add, linear);
+                }
+            }
+            Some(FunctionEnum::Polynomial(poly)) => {
+                for term in &poly.terms {
+                    add(term.ids.clone(), term.coefficient);
+                }
+            }
+            None => bail!("Function is not set"),
+        }
+        for (ids, coefficient) in monomials {
+            if coefficient.abs() <= atol {
+                continue;
+            }
+            let positions =
+                ids.iter()
+                    .map(|id| {
+                        position.get(id).cloned().with_context(|| {
+                            format!("Variable id ({id}) is not found in the ordering")
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+            match positions.as_slice() {
+                [] => dense.constant += coefficient,
+                [i] => dense.linear.push((*i, coefficient)),
+                [i, j] => dense.quadratic.push((*i, *j, coefficient)),
+                _ => dense.higher.push((positions, coefficient)),
+            }
+        }
+        Ok(dense)
+    }
+
+    /// Evaluate once with values given as a dense slice, where `values[i]` is the value of the decision variable `ordering[i]`.
+    ///
+    /// This is only a shorthand of `self.to_dense(ordering, atol)?.evaluate(values)`, and thus compiles the function,
+    /// including a hash map of `ordering`, on every call. For tight loops evaluating the same function many times,
+    /// compile it once by [`Function::to_dense`] and call [`DenseFunction::evaluate`], which only reads the slice.
+    pub fn evaluate_dense(&self, ordering: &[u64], values: &[f64], atol: f64) -> Result<f64> {
+        self.to_dense(ordering, atol)?.evaluate(values)
+    }
+
+    /// Evaluate with interval arithmetic, where each decision variable takes a value in its interval in `intervals`
+    ///
+    /// The returned interval contains all the values of the function, but may not be tight
+    /// since monomials are bounded independently. Decision variables not in `intervals` are regarded as unbounded,
+    /// and unset function is regarded as zero.
+    pub fn evaluate_interval(&self, intervals: &BTreeMap<u64, Bound>) -> Bound {
+        let mut out = Bound {
+            lower: 0.0,
+            upper: 0.0,
+        };
+        for (ids, coefficient) in Polynomial::from(self.clone()).monomials() {
+            let mut term = Bound {
+                lower: coefficient,
+                upper: coefficient,
+            };
+            // `ids` is sorted, and thus the same IDs are consecutive
+            for chunk in ids.chunk_by(|a, b| a == b) {
+                let interval = intervals
+                    .get(&chunk[0])
+                    .cloned()
+                    .unwrap_or_else(Bound::unbounded);
+                term = term.interval_mul(&interval.interval_pow(chunk.len() as i32));
+            }
+            out = out.interval_add(&term);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::Monomial;
+
+    #[test]
+    fn evaluate_dense_matches_evaluate() {
+        let functions: Vec<Function> = vec![
+            FunctionEnum::Constant(2.5).into(),
+            Linear::new([(4, 1.5), (0, -2.0)].into_iter(), 3.0).into(),
+            Quadratic {
+                rows: vec![0, 2, 4],
+                columns: vec![4, 2, 0],
+                values: vec![2.0, -1.0, 0.5],
+                linear: Some(Linear::new([(2, 1.0)].into_iter(), -1.0)),
+            }
+            .into(),
+            FunctionEnum::Polynomial(Polynomial {
+                terms: [
+                    (vec![], 1.0),
+                    (vec![0, 2, 4], 3.0),
+                    (vec![2, 2, 2, 4], -0.5),
+                    (vec![4], 2.0),
+                ]
+                .into_iter()
+                .map(|(ids, coefficient)| Monomial { ids, coefficient })
+                .collect(),
+            })
+            .into(),
+        ];
+        // Unsorted ordering with an ID not used in any function
+        let ordering = [4, 7, 0, 2];
+        let values = [1.5, 100.0, -2.0, 0.75];
+        let state: State = ordering
+            .iter()
+            .cloned()
+            .zip(values.iter().cloned())
+            .collect::<HashMap<_, _>>()
+            .into();
+        for f in functions {
+            let (expected, _) = f.evaluate(&state).unwrap();
+            let dense = f.to_dense(&ordering, 0.0).unwrap();
+            let value = dense.evaluate(&values).unwrap();
+            assert!((value - expected).abs() <= 1e-12, "{value} != {expected}");
+            assert_eq!(f.evaluate_dense(&ordering, &values, 0.0).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn to_dense_errors() {
+        let f: Function = Linear::new([(0, 1.0), (1, 1e-14)].into_iter(), 0.0).into();
+        // Missing and duplicated IDs
+        assert!(f.to_dense(&[0], 0.0).is_err());
+        assert!(f.to_dense(&[0, 1, 0], 0.0).is_err());
+        // Terms within `atol` are dropped, and thus `x1` is no longer required
+        let dense = f.to_dense(&[0], 1e-12).unwrap();
+        assert_eq!(dense.evaluate(&[2.0]).unwrap(), 2.0);
+        assert!(dense.evaluate(&[2.0, 3.0]).is_err());
+    }
+}
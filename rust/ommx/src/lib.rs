@@ -143,16 +143,26 @@
 
 pub use ocipkg;
 
+pub mod adapter;
 pub mod artifact;
+pub mod diff;
+pub mod lp;
+pub mod mps;
+pub mod ortools;
+pub mod qplib;
 pub mod random;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub use prost::Message;
 mod arbitrary;
 mod convert;
 mod evaluate;
 
-pub use evaluate::Evaluate;
+pub use convert::{AuxiliaryRole, ConstraintClass, InstanceBuilder, IntegerEncoding, WeightedCnf};
+pub use evaluate::{DenseFunction, Evaluate};
 
 /// Module created from `ommx.v1` proto files
 pub mod v1 {
+    #![allow(clippy::doc_overindented_list_items)]
     include!("ommx.v1.rs");
 }
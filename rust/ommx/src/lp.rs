@@ -0,0 +1,596 @@
+//! Read an [`Instance`] from the CPLEX LP format
+//!
+//! Decision variables are numbered from zero in the order of their first appearance,
+//! and their names in the file are stored in [`DecisionVariable::name`].
+//! Constraints are also numbered from zero, and their labels are stored in [`Constraint::name`].
+//!
+//! ```rust
+//! use ommx::{lp, v1::{decision_variable::Kind, instance::Sense}};
+//!
+//! let input = r#"
+//! \ A small MIP
+//! Maximize
+//!  obj: 3 x + 2 y - z
+//! Subject To
+//!  c1: x + y + z <= 10
+//!  c2: x - y >= -2
+//! Bounds
+//!  y <= 4
+//!  z free
+//! General
+//!  x
+//! End
+//! "#;
+//! let instance = lp::load_reader(input.as_bytes()).unwrap();
+//! assert_eq!(instance.sense(), Sense::Maximize);
+//! assert_eq!(instance.constraints.len(), 2);
+//!
+//! let x = &instance.decision_variables[0];
+//! assert_eq!(x.name.as_deref(), Some("x"));
+//! assert_eq!(x.kind(), Kind::Integer);
+//! // `0 <= x` is implied
+//! assert_eq!(x.bound.as_ref().unwrap().lower, 0.0);
+//!
+//! let y = instance.decision_variables[1].bound.as_ref().unwrap();
+//! assert_eq!((y.lower, y.upper), (0.0, 4.0));
+//! let z = instance.decision_variables[2].bound.as_ref().unwrap();
+//! assert_eq!((z.lower, z.upper), (f64::NEG_INFINITY, f64::INFINITY));
+//! ```
+
+use crate::v1::{
+    decision_variable::Kind, instance::Sense, Bound, Constraint, DecisionVariable, Equality,
+    Instance, Linear,
+};
+use anyhow::{bail, ensure, Context, Result};
+use std::{collections::BTreeMap, io::Read};
+
+/// Read an instance in the CPLEX LP format from `reader`
+///
+/// - `Maximize`/`Minimize`, `Subject To`, `Bounds`, `General`, `Binary`, and `End` sections and their common aliases are supported.
+/// - Only linear expressions are supported. Quadratic terms `[ ... ]` and other nonlinear constructs result in an error,
+///   as well as `Semi-Continuous` and `SOS` sections.
+/// - Decision variables are continuous with the bound `[0, inf)` unless specified otherwise,
+///   and bounds of binary ones are intersected with `[0, 1]`, e.g. `x = 1` in the `Bounds` section fixes a binary `x` to one.
+/// - `f(x) <= b` and `f(x) = b` are stored as `f(x) - b <= 0` and `f(x) - b = 0`, and `f(x) >= b` as `b - f(x) <= 0`.
+pub fn load_reader(mut reader: impl Read) -> Result<Instance> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    let mut sections: Vec<(Header, String)> = Vec::new();
+    for line in input.lines() {
+        // `\` starts a comment until the end of line
+        let line = line.split('\\').next().unwrap_or_default();
+        match header(line)? {
+            Some((Header::End, _)) => break,
+            Some((header, rest)) => sections.push((header, rest.to_string())),
+            None => match sections.last_mut() {
+                Some((_, text)) => {
+                    text.push('\n');
+                    text.push_str(line);
+                }
+                None => ensure!(
+                    line.trim().is_empty(),
+                    "Content before the objective section: {line}"
+                ),
+            },
+        }
+    }
+
+    let mut builder = Builder::default();
+    let mut sense = None;
+    let mut objective = None;
+    for (header, text) in sections {
+        let mut tokens = Tokens::new(&text)?;
+        match header {
+            Header::Objective(s) => {
+                ensure!(sense.is_none(), "Objective section appears twice");
+                sense = Some(s);
+                // Label of the objective is ignored
+                tokens.label();
+                let (terms, constant) = builder.expression(&mut tokens)?;
+                tokens.ensure_end()?;
+                objective = Some(Linear::new(terms.into_iter(), constant));
+            }
+            Header::Constraints => {
+                while !tokens.is_end() {
+                    builder.constraint(&mut tokens)?;
+                }
+            }
+            Header::Bounds => {
+                while !tokens.is_end() {
+                    builder.bound(&mut tokens)?;
+                }
+            }
+            Header::General | Header::Binary => {
+                let kind = if header == Header::Binary {
+                    Kind::Binary
+                } else {
+                    Kind::Integer
+                };
+                while !tokens.is_end() {
+                    let name = tokens.name()?;
+                    let id = builder.id(&name);
+                    builder.kinds.insert(id, kind);
+                }
+            }
+            Header::End => unreachable!(),
+        }
+    }
+    let sense = sense.context("Objective section is not found")?;
+
+    let decision_variables = builder
+        .names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| {
+            let id = id as u64;
+            let kind = builder.kinds.get(&id).cloned().unwrap_or(Kind::Continuous);
+            let (mut lower, mut upper) = builder
+                .bounds
+                .get(&id)
+                .cloned()
+                .unwrap_or((0.0, f64::INFINITY));
+            if kind == Kind::Binary {
+                lower = lower.max(0.0);
+                upper = upper.min(1.0);
+                ensure!(
+                    lower <= upper,
+                    "Bound of binary decision variable `{name}` does not intersect [0, 1]"
+                );
+            }
+            Ok(DecisionVariable {
+                id,
+                kind: kind as i32,
+                bound: Some(Bound { lower, upper }),
+                name: Some(name.clone()),
+                ..Default::default()
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Instance {
+        decision_variables,
+        objective: objective.map(Into::into),
+        constraints: builder.constraints,
+        sense: sense as i32,
+        ..Default::default()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Header {
+    Objective(Sense),
+    Constraints,
+    Bounds,
+    General,
+    Binary,
+    End,
+}
+
+/// Detect a section header at the beginning of `line`, and returns the header and the rest of the line
+fn header(line: &str) -> Result<Option<(Header, &str)>> {
+    let (first, rest) = split_word(line);
+    let header = match first.to_lowercase().as_str() {
+        "maximize" | "maximise" | "maximum" | "max" => Header::Objective(Sense::Maximize),
+        "minimize" | "minimise" | "minimum" | "min" => Header::Objective(Sense::Minimize),
+        "st" | "st." | "s.t." => Header::Constraints,
+        lower @ ("subject" | "such") => {
+            let (second, rest) = split_word(rest);
+            let expected = if lower == "subject" { "to" } else { "that" };
+            if second.to_lowercase() != expected {
+                return Ok(None);
+            }
+            return Ok(Some((Header::Constraints, rest)));
+        }
+        "bound" | "bounds" => Header::Bounds,
+        "general" | "generals" | "gen" => Header::General,
+        "binary" | "binaries" | "bin" => Header::Binary,
+        "end" => Header::End,
+        "semi-continuous" | "semis" | "semi" | "sos" => {
+            bail!("{first} section is not supported")
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((header, rest)))
+}
+
+fn split_word(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    line.split_once(char::is_whitespace).unwrap_or((line, ""))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Le,
+    Ge,
+    Eq,
+}
+
+impl Op {
+    /// Operator with swapped sides, e.g. `a <= b` is `b >= a`
+    fn reversed(self) -> Self {
+        match self {
+            Op::Le => Op::Ge,
+            Op::Ge => Op::Le,
+            Op::Eq => Op::Eq,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    /// `+` or `-`
+    Sign(f64),
+    Colon,
+    Op(Op),
+    /// Tokens of unsupported constructs, e.g. `[`, `^`, or `*` of quadratic terms
+    Other(char),
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || "_!\"#$%&(),;?@'{}|~".contains(c)
+}
+
+struct Tokens {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Tokens {
+    fn new(text: &str) -> Result<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let start = i;
+            i += 1;
+            match c {
+                c if c.is_whitespace() => {}
+                '+' => tokens.push(Token::Sign(1.0)),
+                '-' => tokens.push(Token::Sign(-1.0)),
+                ':' => tokens.push(Token::Colon),
+                '<' | '>' | '=' => {
+                    if i < chars.len() && "<>=".contains(chars[i]) {
+                        i += 1;
+                    }
+                    let op: String = chars[start..i].iter().collect();
+                    tokens.push(Token::Op(match op.as_str() {
+                        "<" | "<=" | "=<" => Op::Le,
+                        ">" | ">=" | "=>" => Op::Ge,
+                        "=" => Op::Eq,
+                        _ => bail!("Invalid operator: {op}"),
+                    }));
+                }
+                c if c.is_ascii_digit() || c == '.' => {
+                    while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                        i += 1;
+                    }
+                    // Exponent, e.g. `1e-3`
+                    if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                        let mut j = i + 1;
+                        if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                            j += 1;
+                        }
+                        if j < chars.len() && chars[j].is_ascii_digit() {
+                            i = j;
+                            while i < chars.len() && chars[i].is_ascii_digit() {
+                                i += 1;
+                            }
+                        }
+                    }
+                    let number: String = chars[start..i].iter().collect();
+                    tokens.push(Token::Number(
+                        number
+                            .parse()
+                            .with_context(|| format!("Invalid number: {number}"))?,
+                    ));
+                }
+                c if is_name_start(c) => {
+                    while i < chars.len()
+                        && (is_name_start(chars[i]) || chars[i].is_ascii_digit() || chars[i] == '.')
+                    {
+                        i += 1;
+                    }
+                    tokens.push(Token::Name(chars[start..i].iter().collect()));
+                }
+                c => tokens.push(Token::Other(c)),
+            }
+        }
+        Ok(Self { tokens, pos: 0 })
+    }
+
+    fn peek(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn ensure_end(&self) -> Result<()> {
+        if let Some(token) = self.peek(0) {
+            bail!("Unexpected token: {token:?}");
+        }
+        Ok(())
+    }
+
+    /// Consume a label `name:` if exists
+    fn label(&mut self) -> Option<String> {
+        match (self.peek(0), self.peek(1)) {
+            (Some(Token::Name(name)), Some(Token::Colon)) => {
+                let name = name.clone();
+                self.pos += 2;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            token => bail!("Expected a name, but found {token:?}"),
+        }
+    }
+
+    fn op(&mut self) -> Result<Op> {
+        match self.next() {
+            Some(Token::Op(op)) => Ok(op),
+            token => bail!("Expected an operator, but found {token:?}"),
+        }
+    }
+
+    /// Signed number, where `inf` and `infinity` are regarded as infinity
+    fn value(&mut self) -> Result<f64> {
+        let mut sign = 1.0;
+        while let Some(Token::Sign(s)) = self.peek(0) {
+            sign *= s;
+            self.pos += 1;
+        }
+        match self.next() {
+            Some(Token::Number(value)) => Ok(sign * value),
+            Some(Token::Name(name)) if is_infinity(&name) => Ok(sign * f64::INFINITY),
+            token => bail!("Expected a number, but found {token:?}"),
+        }
+    }
+
+    fn starts_with_value(&self) -> bool {
+        match self.peek(0) {
+            Some(Token::Sign(_)) | Some(Token::Number(_)) => true,
+            Some(Token::Name(name)) => is_infinity(name),
+            _ => false,
+        }
+    }
+}
+
+fn is_infinity(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "inf" | "infinity")
+}
+
+#[derive(Default)]
+struct Builder {
+    /// Names of decision variables indexed by their IDs
+    names: Vec<String>,
+    ids: BTreeMap<String, u64>,
+    kinds: BTreeMap<u64, Kind>,
+    bounds: BTreeMap<u64, (f64, f64)>,
+    constraints: Vec<Constraint>,
+}
+
+impl Builder {
+    fn id(&mut self, name: &str) -> u64 {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len() as u64;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Linear expression as terms and a constant, e.g. `3 x + 2 y - 1`
+    fn expression(&mut self, tokens: &mut Tokens) -> Result<(BTreeMap<u64, f64>, f64)> {
+        let mut terms = BTreeMap::new();
+        let mut constant = 0.0;
+        let mut first = true;
+        loop {
+            let mut sign = None;
+            while let Some(Token::Sign(s)) = tokens.peek(0) {
+                sign = Some(sign.unwrap_or(1.0) * s);
+                tokens.pos += 1;
+            }
+            if !first && sign.is_none() {
+                break;
+            }
+            let sign = sign.unwrap_or(1.0);
+            match (tokens.peek(0).cloned(), tokens.peek(1)) {
+                (Some(Token::Number(coefficient)), Some(Token::Name(name)))
+                    if !is_infinity(name) =>
+                {
+                    let id = self.id(name);
+                    *terms.entry(id).or_insert(0.0) += sign * coefficient;
+                    tokens.pos += 2;
+                }
+                (Some(Token::Number(value)), _) => {
+                    constant += sign * value;
+                    tokens.pos += 1;
+                }
+                (Some(Token::Name(name)), next) if next != Some(&Token::Colon) => {
+                    let id = self.id(&name);
+                    *terms.entry(id).or_insert(0.0) += sign;
+                    tokens.pos += 1;
+                }
+                (Some(Token::Other(c)), _) => {
+                    bail!("Unsupported token '{c}': quadratic and other nonlinear terms are not supported yet")
+                }
+                (token, _) => {
+                    ensure!(first, "Expected a term, but found {token:?}");
+                    break;
+                }
+            }
+            first = false;
+        }
+        if let Some(Token::Other(c)) = tokens.peek(0) {
+            bail!("Unsupported token '{c}': quadratic and other nonlinear terms are not supported yet");
+        }
+        Ok((terms, constant))
+    }
+
+    /// Constraint `[label:] expression op value`
+    fn constraint(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let name = tokens.label();
+        let (terms, constant) = self.expression(tokens)?;
+        let op = tokens.op()?;
+        let rhs = tokens.value()?;
+        ensure!(
+            rhs.is_finite(),
+            "Right-hand side of a constraint must be finite: {rhs}"
+        );
+        let (sign, equality) = match op {
+            Op::Le => (1.0, Equality::LessThanOrEqualToZero),
+            Op::Ge => (-1.0, Equality::LessThanOrEqualToZero),
+            Op::Eq => (1.0, Equality::EqualToZero),
+        };
+        let function = Linear::new(
+            terms.into_iter().map(|(id, c)| (id, sign * c)),
+            sign * (constant - rhs),
+        );
+        self.constraints.push(Constraint {
+            id: self.constraints.len() as u64,
+            equality: equality as i32,
+            function: Some(function.into()),
+            name,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Bound `name free`, `name op value`, `value op name`, or `value op name op value`
+    fn bound(&mut self, tokens: &mut Tokens) -> Result<()> {
+        if tokens.starts_with_value() {
+            let value = tokens.value()?;
+            let op = tokens.op()?;
+            let id = self.id(&tokens.name()?);
+            self.set_bound(id, op.reversed(), value);
+            if let Some(Token::Op(_)) = tokens.peek(0) {
+                let op = tokens.op()?;
+                let value = tokens.value()?;
+                self.set_bound(id, op, value);
+            }
+            return Ok(());
+        }
+        let id = self.id(&tokens.name()?);
+        if let Some(Token::Name(name)) = tokens.peek(0) {
+            if name.to_lowercase() == "free" {
+                tokens.pos += 1;
+                self.bounds.insert(id, (f64::NEG_INFINITY, f64::INFINITY));
+                return Ok(());
+            }
+        }
+        let op = tokens.op()?;
+        let value = tokens.value()?;
+        self.set_bound(id, op, value);
+        Ok(())
+    }
+
+    /// Set a bound of `x op value`
+    fn set_bound(&mut self, id: u64, op: Op, value: f64) {
+        let (lower, upper) = self.bounds.entry(id).or_insert((0.0, f64::INFINITY));
+        match op {
+            Op::Le => *upper = value,
+            Op::Ge => *lower = value,
+            Op::Eq => {
+                *lower = value;
+                *upper = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound(instance: &Instance, id: usize) -> (f64, f64) {
+        let bound = instance.decision_variables[id].bound.as_ref().unwrap();
+        (bound.lower, bound.upper)
+    }
+
+    #[test]
+    fn mip_bounds() {
+        let input = r#"
+Minimize
+ obj: x + 2 y - z + w
+Subject To
+ c1: x + y + z + w >= -3
+Bounds
+ x free
+ -inf <= y <= 5
+ z <= 8
+General
+ z
+End
+"#;
+        let instance = load_reader(input.as_bytes()).unwrap();
+        assert_eq!(instance.sense(), Sense::Minimize);
+        assert_eq!(bound(&instance, 0), (f64::NEG_INFINITY, f64::INFINITY));
+        assert_eq!(bound(&instance, 1), (f64::NEG_INFINITY, 5.0));
+        assert_eq!(bound(&instance, 2), (0.0, 8.0));
+        assert_eq!(bound(&instance, 3), (0.0, f64::INFINITY));
+        let kinds: Vec<Kind> = instance
+            .decision_variables
+            .iter()
+            .map(|v| v.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Kind::Continuous,
+                Kind::Continuous,
+                Kind::Integer,
+                Kind::Continuous
+            ]
+        );
+        // `x + y + z + w >= -3` is `-3 - (x + y + z + w) <= 0`
+        let c = &instance.constraints[0];
+        assert_eq!(c.equality(), Equality::LessThanOrEqualToZero);
+        assert_eq!(
+            c.function,
+            Some(Linear::new((0..4).map(|id| (id, -1.0)), -3.0).into())
+        );
+        instance.verify_invariants().unwrap();
+    }
+
+    #[test]
+    fn binary_bounds() {
+        let input = r#"
+Maximize
+ obj: x + y + z
+Bounds
+ x = 1
+ -2 <= y <= 3
+Binary
+ x y z
+End
+"#;
+        let instance = load_reader(input.as_bytes()).unwrap();
+        assert_eq!(bound(&instance, 0), (1.0, 1.0));
+        assert_eq!(bound(&instance, 1), (0.0, 1.0));
+        assert_eq!(bound(&instance, 2), (0.0, 1.0));
+        assert!(instance
+            .decision_variables
+            .iter()
+            .all(|v| v.kind() == Kind::Binary));
+
+        let input = "Maximize\n obj: x\nBounds\n x >= 2\nBinary\n x\nEnd\n";
+        assert!(load_reader(input.as_bytes()).is_err());
+    }
+}
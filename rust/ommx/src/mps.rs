@@ -0,0 +1,299 @@
+//! Write an [`Instance`] in the free MPS format
+//!
+//! Decision variables are written as columns named `x{id}`, constraints as rows named `c{id}`,
+//! and the objective as the row `OBJ`, so that the IDs can be recovered from the names.
+//!
+//! ```rust
+//! use ommx::{mps, v1::{instance::Sense, Instance}};
+//! use maplit::btreemap;
+//!
+//! let instance = Instance::from_qubo(&btreemap! { (0, 0) => 1.0, (0, 1) => -2.0 }, 0.0, Sense::Minimize).unwrap();
+//! let mut out = Vec::new();
+//! mps::write(&instance, &mut out).unwrap();
+//! let out = String::from_utf8(out).unwrap();
+//! assert!(out.contains("QUADOBJ"));
+//! assert!(out.contains(" BV BND x0"));
+//! ```
+
+use crate::v1::{decision_variable::Kind, instance::Sense, Bound, Equality, Instance, Polynomial};
+use anyhow::{bail, Context, Result};
+use std::{collections::BTreeMap, io::Write};
+
+const OBJECTIVE_ROW: &str = "OBJ";
+
+/// Write `instance` in the free MPS format
+///
+/// - The objective must be quadratic or lower, and the quadratic part is written in the `QUADOBJ` section.
+/// - Constraints must be linear. `f(x) = 0` and `f(x) <= 0` are written as `E` and `L` rows, and thus no `RANGES` section is needed.
+/// - The constant term of the objective is written as the negated right-hand side of the objective row.
+/// - Binary, integer, and continuous variables are supported, and integer ones are surrounded by `INTORG` and `INTEND` markers.
+///   Bounds are always written explicitly since the default bound of MPS is `[0, inf)`.
+pub fn write(instance: &Instance, mut writer: impl Write) -> Result<()> {
+    let sense = instance.sense();
+    let objective = instance
+        .objective
+        .as_ref()
+        .context("Objective is not set")?;
+    let mut objective_constant = 0.0;
+    let mut quadratic = BTreeMap::new();
+    // Column-wise coefficients `column ID -> [(row name, coefficient)]`
+    let mut columns: BTreeMap<u64, Vec<(String, f64)>> = instance
+        .decision_variables
+        .iter()
+        .map(|v| (v.id, Vec::new()))
+        .collect();
+    fn column(
+        columns: &mut BTreeMap<u64, Vec<(String, f64)>>,
+        id: u64,
+    ) -> Result<&mut Vec<(String, f64)>> {
+        columns
+            .get_mut(&id)
+            .with_context(|| format!("Decision variable ({id}) is used but not defined"))
+    }
+    for (ids, coefficient) in Polynomial::from(objective.clone()).monomials() {
+        match ids.as_slice() {
+            [] => objective_constant += coefficient,
+            [i] => column(&mut columns, *i)?.push((OBJECTIVE_ROW.to_string(), coefficient)),
+            [i, j] => {
+                column(&mut columns, *i)?;
+                column(&mut columns, *j)?;
+                *quadratic.entry((*i, *j)).or_insert(0.0) += coefficient;
+            }
+            _ => bail!(
+                "MPS does not support a term of degree {}: {ids:?}",
+                ids.len()
+            ),
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut rhs = Vec::new();
+    let mut constraints: Vec<_> = instance.constraints.iter().collect();
+    constraints.sort_by_key(|c| c.id);
+    for c in constraints {
+        let row = format!("c{}", c.id);
+        let (linear, b) = c
+            .as_standard_form()
+            .context("MPS does not support a non-linear constraint")?;
+        for term in linear.terms {
+            column(&mut columns, term.id)?.push((row.clone(), term.coefficient));
+        }
+        let row_type = match c.equality() {
+            Equality::EqualToZero => "E",
+            Equality::LessThanOrEqualToZero => "L",
+            Equality::Unspecified => bail!("Equality of constraint ({}) is not specified", c.id),
+        };
+        rows.push((row_type, row.clone()));
+        if b != 0.0 {
+            rhs.push((row, b));
+        }
+    }
+    if objective_constant != 0.0 {
+        rhs.push((OBJECTIVE_ROW.to_string(), -objective_constant));
+    }
+
+    let name = instance
+        .description
+        .as_ref()
+        .and_then(|d| d.name.as_deref())
+        .unwrap_or("ommx");
+    writeln!(writer, "NAME {}", name.replace(char::is_whitespace, "_"))?;
+    writeln!(writer, "OBJSENSE")?;
+    match sense {
+        Sense::Minimize => writeln!(writer, "    MIN")?,
+        Sense::Maximize => writeln!(writer, "    MAX")?,
+        Sense::Unspecified => bail!("Sense is not specified"),
+    }
+    writeln!(writer, "ROWS")?;
+    writeln!(writer, " N  {OBJECTIVE_ROW}")?;
+    for (row_type, row) in &rows {
+        writeln!(writer, " {row_type}  {row}")?;
+    }
+
+    writeln!(writer, "COLUMNS")?;
+    let mut decision_variables: Vec<_> = instance.decision_variables.iter().collect();
+    decision_variables.sort_by_key(|v| v.id);
+    let mut is_integer = false;
+    for v in &decision_variables {
+        let (integer, can_be_zero) = v.kind().to_variable_flags()?;
+        if can_be_zero {
+            bail!(
+                "MPS does not support semi-variables: decision variable ({})",
+                v.id
+            );
+        }
+        if integer != is_integer {
+            let marker = if integer { "INTORG" } else { "INTEND" };
+            writeln!(writer, "    MARKER  'MARKER'  '{marker}'")?;
+            is_integer = integer;
+        }
+        let entries = &columns[&v.id];
+        if entries.is_empty() {
+            // Declare the column even if it does not appear in any rows
+            writeln!(writer, "    x{}  {OBJECTIVE_ROW}  0", v.id)?;
+        }
+        for (row, coefficient) in entries {
+            writeln!(writer, "    x{}  {row}  {coefficient}", v.id)?;
+        }
+    }
+    if is_integer {
+        writeln!(writer, "    MARKER  'MARKER'  'INTEND'")?;
+    }
+
+    writeln!(writer, "RHS")?;
+    for (row, value) in &rhs {
+        writeln!(writer, "    RHS  {row}  {value}")?;
+    }
+
+    writeln!(writer, "BOUNDS")?;
+    for v in &decision_variables {
+        let Bound { lower, upper } = v.effective_bound();
+        let x = format!("x{}", v.id);
+        match v.kind() {
+            Kind::Binary if lower == 0.0 && upper == 1.0 => {
+                writeln!(writer, " BV BND {x}")?;
+            }
+            kind => {
+                let (lower_type, upper_type) = if kind == Kind::Continuous {
+                    ("LO", "UP")
+                } else {
+                    ("LI", "UI")
+                };
+                if lower == f64::NEG_INFINITY && upper == f64::INFINITY {
+                    writeln!(writer, " FR BND {x}")?;
+                    continue;
+                }
+                if lower == f64::NEG_INFINITY {
+                    writeln!(writer, " MI BND {x}")?;
+                } else {
+                    writeln!(writer, " {lower_type} BND {x}  {lower}")?;
+                }
+                if upper == f64::INFINITY {
+                    writeln!(writer, " PL BND {x}")?;
+                } else {
+                    writeln!(writer, " {upper_type} BND {x}  {upper}")?;
+                }
+            }
+        }
+    }
+
+    if !quadratic.is_empty() {
+        // `QUADOBJ` represents `x^T Q x / 2` by the lower triangular part of the symmetric `Q`
+        writeln!(writer, "QUADOBJ")?;
+        for ((i, j), coefficient) in quadratic {
+            let value = if i == j {
+                2.0 * coefficient
+            } else {
+                coefficient
+            };
+            writeln!(writer, "    x{j}  x{i}  {value}")?;
+        }
+    }
+    writeln!(writer, "ENDATA")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{Bound, Constraint, DecisionVariable, Linear, Quadratic};
+    use maplit::btreemap;
+
+    fn variable(id: u64, kind: Kind, bound: Option<(f64, f64)>) -> DecisionVariable {
+        DecisionVariable {
+            id,
+            kind: kind as i32,
+            bound: bound.map(|(lower, upper)| Bound { lower, upper }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mixed_integer_quadratic() {
+        // Minimize `2 x0^2 + 3 x0 x1 + x2 - x3 + 4 x4 + 10`
+        // subject to `x0 + x1 - 3 <= 0` and `x2 + 2 x3 - x4 = 0`
+        let instance = Instance {
+            decision_variables: vec![
+                variable(0, Kind::Continuous, Some((f64::NEG_INFINITY, 5.0))),
+                variable(1, Kind::Integer, Some((-3.0, 7.0))),
+                variable(2, Kind::Integer, Some((0.0, f64::INFINITY))),
+                variable(3, Kind::Continuous, None),
+                variable(4, Kind::Binary, None),
+                // Not used in any rows
+                variable(5, Kind::Continuous, Some((1.0, 2.0))),
+            ],
+            objective: Some(
+                Quadratic::from_terms(
+                    btreemap! { (0, 0) => 2.0, (0, 1) => 3.0 },
+                    Some(Linear::new(
+                        [(2, 1.0), (3, -1.0), (4, 4.0)].into_iter(),
+                        10.0,
+                    )),
+                )
+                .into(),
+            ),
+            constraints: vec![
+                Constraint {
+                    id: 2,
+                    equality: Equality::EqualToZero as i32,
+                    function: Some(
+                        Linear::new([(2, 1.0), (3, 2.0), (4, -1.0)].into_iter(), 0.0).into(),
+                    ),
+                    ..Default::default()
+                },
+                Constraint {
+                    id: 0,
+                    equality: Equality::LessThanOrEqualToZero as i32,
+                    function: Some(Linear::new([(0, 1.0), (1, 1.0)].into_iter(), -3.0).into()),
+                    ..Default::default()
+                },
+            ],
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write(&instance, &mut out).unwrap();
+        let expected = "\
+NAME ommx
+OBJSENSE
+    MIN
+ROWS
+ N  OBJ
+ L  c0
+ E  c2
+COLUMNS
+    x0  c0  1
+    MARKER  'MARKER'  'INTORG'
+    x1  c0  1
+    x2  OBJ  1
+    x2  c2  1
+    MARKER  'MARKER'  'INTEND'
+    x3  OBJ  -1
+    x3  c2  2
+    MARKER  'MARKER'  'INTORG'
+    x4  OBJ  4
+    x4  c2  -1
+    MARKER  'MARKER'  'INTEND'
+    x5  OBJ  0
+RHS
+    RHS  c0  3
+    RHS  OBJ  -10
+BOUNDS
+ MI BND x0
+ UP BND x0  5
+ LI BND x1  -3
+ UI BND x1  7
+ LI BND x2  0
+ PL BND x2
+ FR BND x3
+ BV BND x4
+ LO BND x5  1
+ UP BND x5  2
+QUADOBJ
+    x0  x0  4
+    x1  x0  3
+ENDATA
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
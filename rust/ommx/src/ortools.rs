@@ -0,0 +1,255 @@
+//! Export to the [MathOpt](https://developers.google.com/optimization/math_opt) model of Google OR-Tools
+//!
+//! The model is written in the JSON mapping of `operations_research.math_opt.ModelProto`,
+//! which OR-Tools can read without a native binding.
+//!
+//! ```rust
+//! use ommx::{ortools, v1::{decision_variable::Kind, instance::Sense, Bound, Constraint, DecisionVariable, Equality, Instance, Linear, Quadratic}};
+//! use maplit::btreemap;
+//! use serde_json::{json, Value};
+//!
+//! let variable = |id, kind: Kind, bound: Option<Bound>| DecisionVariable { id, kind: kind as i32, bound, ..Default::default() };
+//! let instance = Instance {
+//!     decision_variables: vec![
+//!         variable(0, Kind::Binary, None),
+//!         variable(1, Kind::Integer, Some(Bound { lower: -2.0, upper: 5.0 })),
+//!         variable(2, Kind::Continuous, None),
+//!     ],
+//!     // `3 x1 x0 + x2^2 - x1 + 4`, where `x1 x0` is given in the lower triangle
+//!     objective: Some(Quadratic::from_terms(
+//!         btreemap! { (1, 0) => 3.0, (2, 2) => 1.0 },
+//!         Some(Linear::new([(1, -1.0)].into_iter(), 4.0)),
+//!     ).into()),
+//!     constraints: vec![
+//!         // `x0 + x1 - 2 = 0`
+//!         Constraint {
+//!             id: 0,
+//!             equality: Equality::EqualToZero as i32,
+//!             function: Some(Linear::new([(0, 1.0), (1, 1.0)].into_iter(), -2.0).into()),
+//!             ..Default::default()
+//!         },
+//!         // `2 x2 + 1 <= 0`
+//!         Constraint {
+//!             id: 1,
+//!             equality: Equality::LessThanOrEqualToZero as i32,
+//!             function: Some(Linear::new([(2, 2.0)].into_iter(), 1.0).into()),
+//!             ..Default::default()
+//!         },
+//!     ],
+//!     sense: Sense::Maximize as i32,
+//!     ..Default::default()
+//! };
+//! let model: Value = serde_json::from_str(&ortools::to_mathopt_json(&instance).unwrap()).unwrap();
+//!
+//! assert_eq!(model["variables"]["ids"], json!([0, 1, 2]));
+//! assert_eq!(model["variables"]["lowerBounds"], json!([0.0, -2.0, "-Infinity"]));
+//! assert_eq!(model["variables"]["upperBounds"], json!([1.0, 5.0, "Infinity"]));
+//! assert_eq!(model["variables"]["integers"], json!([true, true, false]));
+//!
+//! let objective = &model["objective"];
+//! assert_eq!(objective["maximize"], json!(true));
+//! assert_eq!(objective["offset"], json!(4.0));
+//! assert_eq!(objective["linearCoefficients"], json!({ "ids": [1], "values": [-1.0] }));
+//! assert_eq!(
+//!     objective["quadraticCoefficients"],
+//!     json!({ "rowIds": [0, 2], "columnIds": [1, 2], "coefficients": [3.0, 1.0] })
+//! );
+//!
+//! // `x0 + x1 = 2` and `-inf <= 2 x2 <= -1`
+//! assert_eq!(model["linearConstraints"]["ids"], json!([0, 1]));
+//! assert_eq!(model["linearConstraints"]["lowerBounds"], json!([2.0, "-Infinity"]));
+//! assert_eq!(model["linearConstraints"]["upperBounds"], json!([2.0, -1.0]));
+//! assert_eq!(
+//!     model["linearConstraintMatrix"],
+//!     json!({ "rowIds": [0, 0, 1], "columnIds": [0, 1, 2], "coefficients": [1.0, 1.0, 2.0] })
+//! );
+//! ```
+
+use crate::v1::{decision_variable::Kind, instance::Sense, Bound, Equality, Instance, Polynomial};
+use anyhow::{bail, ensure, Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Convert into the JSON of MathOpt `ModelProto`
+///
+/// The objective must be quadratic or lower, and constraints must be linear.
+/// Binary decision variables are integer variables in `[0, 1]`, and semi-variables are not supported.
+/// This fails if the objective or constraints use decision variables not defined in the instance.
+pub fn to_mathopt_json(instance: &Instance) -> Result<String> {
+    let mut decision_variables: Vec<_> = instance.decision_variables.iter().collect();
+    decision_variables.sort_by_key(|v| v.id);
+    let mut ids = Vec::new();
+    let mut lower_bounds = Vec::new();
+    let mut upper_bounds = Vec::new();
+    let mut integers = Vec::new();
+    let mut names = Vec::new();
+    for v in decision_variables {
+        let kind = v.kind();
+        if !matches!(kind, Kind::Binary | Kind::Integer | Kind::Continuous) {
+            bail!("Unsupported kind of decision variable ({}): {kind:?}", v.id);
+        }
+        let Bound { lower, upper } = v.effective_bound();
+        ids.push(v.id);
+        lower_bounds.push(number(lower)?);
+        upper_bounds.push(number(upper)?);
+        integers.push(kind != Kind::Continuous);
+        names.push(v.name.clone().unwrap_or_default());
+    }
+    for id in instance.used_decision_variable_ids() {
+        ensure!(
+            ids.binary_search(&id).is_ok(),
+            "Decision variable ({id}) is used but not defined"
+        );
+    }
+
+    let maximize = match instance.sense() {
+        Sense::Minimize => false,
+        Sense::Maximize => true,
+        Sense::Unspecified => bail!("Sense is not specified"),
+    };
+    let objective = instance
+        .objective
+        .as_ref()
+        .context("Objective is not set")?;
+    let mut offset = 0.0;
+    let mut linear = BTreeMap::new();
+    let mut quadratic = BTreeMap::new();
+    for (ids, coefficient) in Polynomial::from(objective.clone()).monomials() {
+        match ids.as_slice() {
+            [] => offset += coefficient,
+            [i] => *linear.entry(*i).or_insert(0.0) += coefficient,
+            // `ids` is sorted, and thus this is the upper triangular part required by MathOpt
+            [i, j] => *quadratic.entry((*i, *j)).or_insert(0.0) += coefficient,
+            _ => bail!(
+                "MathOpt does not support a term of degree {}: {ids:?}",
+                ids.len()
+            ),
+        }
+    }
+
+    let mut constraints: Vec<_> = instance.constraints.iter().collect();
+    constraints.sort_by_key(|c| c.id);
+    let mut constraint_ids = Vec::new();
+    let mut constraint_lower_bounds = Vec::new();
+    let mut constraint_upper_bounds = Vec::new();
+    let mut constraint_names = Vec::new();
+    let mut matrix = BTreeMap::new();
+    for c in constraints {
+        let (linear, b) = c
+            .as_standard_form()
+            .context("MathOpt does not support a non-linear constraint")?;
+        for term in linear.terms {
+            matrix.insert((c.id, term.id), term.coefficient);
+        }
+        let lower = match c.equality() {
+            Equality::EqualToZero => b,
+            Equality::LessThanOrEqualToZero => f64::NEG_INFINITY,
+            Equality::Unspecified => bail!("Equality of constraint ({}) is not specified", c.id),
+        };
+        constraint_ids.push(c.id);
+        constraint_lower_bounds.push(number(lower)?);
+        constraint_upper_bounds.push(number(b)?);
+        constraint_names.push(c.name.clone().unwrap_or_default());
+    }
+
+    let model = json!({
+        "name": instance
+            .description
+            .as_ref()
+            .and_then(|d| d.name.clone())
+            .unwrap_or_default(),
+        "variables": {
+            "ids": ids,
+            "lowerBounds": lower_bounds,
+            "upperBounds": upper_bounds,
+            "integers": integers,
+            "names": names,
+        },
+        "objective": {
+            "maximize": maximize,
+            "offset": number(offset)?,
+            "linearCoefficients": {
+                "ids": linear.keys().collect::<Vec<_>>(),
+                "values": linear.values().map(|v| number(*v)).collect::<Result<Vec<_>>>()?,
+            },
+            "quadraticCoefficients": sparse_matrix(quadratic)?,
+        },
+        "linearConstraints": {
+            "ids": constraint_ids,
+            "lowerBounds": constraint_lower_bounds,
+            "upperBounds": constraint_upper_bounds,
+            "names": constraint_names,
+        },
+        "linearConstraintMatrix": sparse_matrix(matrix)?,
+    });
+    Ok(serde_json::to_string(&model)?)
+}
+
+/// JSON number, where infinities are the strings `"Infinity"` and `"-Infinity"` as the JSON mapping of protobuf
+fn number(value: f64) -> Result<Value> {
+    ensure!(!value.is_nan(), "NaN cannot be written");
+    Ok(if value == f64::INFINITY {
+        json!("Infinity")
+    } else if value == f64::NEG_INFINITY {
+        json!("-Infinity")
+    } else {
+        json!(value)
+    })
+}
+
+/// `SparseDoubleMatrixProto` sorted by `(row, column)`
+fn sparse_matrix(matrix: BTreeMap<(u64, u64), f64>) -> Result<Value> {
+    let mut row_ids = Vec::new();
+    let mut column_ids = Vec::new();
+    let mut coefficients = Vec::new();
+    for ((row, column), coefficient) in matrix {
+        row_ids.push(row);
+        column_ids.push(column);
+        coefficients.push(number(coefficient)?);
+    }
+    Ok(json!({
+        "rowIds": row_ids,
+        "columnIds": column_ids,
+        "coefficients": coefficients,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{Constraint, DecisionVariable, Linear};
+
+    #[test]
+    fn undefined_decision_variable() {
+        let mut instance = Instance {
+            decision_variables: vec![DecisionVariable {
+                id: 0,
+                kind: Kind::Continuous as i32,
+                ..Default::default()
+            }],
+            objective: Some(Linear::new([(0, 1.0), (1, 2.0)].into_iter(), 0.0).into()),
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        };
+        let err = to_mathopt_json(&instance).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Decision variable (1) is used but not defined"
+        );
+
+        instance.objective = Some(Linear::new([(0, 1.0)].into_iter(), 0.0).into());
+        assert!(to_mathopt_json(&instance).is_ok());
+
+        instance.constraints.push(Constraint {
+            id: 0,
+            equality: Equality::LessThanOrEqualToZero as i32,
+            function: Some(Linear::new([(2, 1.0)].into_iter(), -1.0).into()),
+            ..Default::default()
+        });
+        let err = to_mathopt_json(&instance).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Decision variable (2) is used but not defined"
+        );
+    }
+}
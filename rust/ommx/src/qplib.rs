@@ -0,0 +1,661 @@
+//! Write an [`Instance`] in the QPLIB format
+//!
+//! Decision variables and constraints are written in the order of their IDs,
+//! and named `x{id}` and `c{id}` in the names section so that the IDs can be recovered.
+//!
+//! ```rust
+//! use ommx::{qplib, v1::{instance::Sense, Instance}};
+//! use maplit::btreemap;
+//!
+//! let instance = Instance::from_qubo(&btreemap! { (0, 0) => 1.0, (0, 1) => -2.0 }, 0.0, Sense::Minimize).unwrap();
+//! let mut out = Vec::new();
+//! qplib::write(&instance, &mut out).unwrap();
+//! let out = String::from_utf8(out).unwrap();
+//! let mut lines = out.lines();
+//! assert_eq!(lines.next(), Some("ommx # problem name"));
+//! assert_eq!(lines.next(), Some("QBN # problem type"));
+//! assert_eq!(lines.next(), Some("minimize # objective sense"));
+//! ```
+
+use crate::{
+    convert::is_hessian_positive_semidefinite,
+    v1::{decision_variable::Kind, instance::Sense, Equality, Function, Instance, Polynomial},
+};
+use anyhow::{bail, Context, Result};
+use std::{collections::BTreeMap, io::Write};
+
+/// Value written for infinite bounds and sides
+const INFINITY: &str = "1.0E+30";
+
+/// Tolerance of the smallest eigenvalue to determine the convexity of quadratic functions
+const CONVEXITY_ATOL: f64 = 1e-9;
+
+/// Write `instance` in the QPLIB format
+///
+/// - The three-letter problem type is derived from the objective, the kinds of decision variables, and the constraints.
+///   Objective is `L` (linear), `D` (convex for minimization, concave for maximization, with a diagonal Hessian),
+///   `C` (convex or concave as `D` with a non-diagonal Hessian), or `Q` (other quadratic),
+///   decision variables are `C` (continuous), `B` (binary), `M` (binary and continuous), `I` (integer), or `G` (other mixed),
+///   and constraints are `N` (none), `B` (box), `L` (linear), `D` (convex quadratic with diagonal Hessians),
+///   `C` (other convex quadratic), or `Q` (other quadratic).
+/// - Functions must be quadratic or lower, and semi-continuous or semi-integer decision variables are not supported.
+/// - `f(x) <= 0` and `f(x) = 0` are written as `-inf <= f(x) - c <= -c` and `-c <= f(x) - c <= -c` for the constant term `c`.
+/// - Quadratic terms are written as the lower triangular part of the symmetric `Q` of `x^T Q x / 2`.
+pub fn write(instance: &Instance, mut writer: impl Write) -> Result<()> {
+    let sense = instance.sense();
+    let sign = match sense {
+        Sense::Minimize => 1.0,
+        Sense::Maximize => -1.0,
+        Sense::Unspecified => bail!("Sense is not specified"),
+    };
+    let mut decision_variables: Vec<_> = instance.decision_variables.iter().collect();
+    decision_variables.sort_by_key(|v| v.id);
+    let index: BTreeMap<u64, usize> = decision_variables
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (v.id, i + 1))
+        .collect();
+    let mut constraints: Vec<_> = instance.constraints.iter().collect();
+    constraints.sort_by_key(|c| c.id);
+
+    let objective = Terms::new(
+        instance
+            .objective
+            .as_ref()
+            .context("Objective is not set")?,
+        &index,
+    )?;
+    let objective_type = if objective.quadratic.is_empty() {
+        'L'
+    } else if objective.is_diagonal_convex(sign) {
+        'D'
+    } else if objective.is_convex(sign) {
+        'C'
+    } else {
+        'Q'
+    };
+
+    let mut variable_types = Vec::new();
+    for v in &decision_variables {
+        let ty = match v.kind() {
+            Kind::Continuous => 0,
+            Kind::Integer => 1,
+            Kind::Binary => 2,
+            kind => bail!(
+                "QPLIB does not support {kind:?} decision variable ({})",
+                v.id
+            ),
+        };
+        variable_types.push(ty);
+    }
+    let has = |ty| variable_types.contains(&ty);
+    let variable_type = match (has(0), has(1), has(2)) {
+        (_, false, false) => 'C',
+        (false, false, true) => 'B',
+        (true, false, true) => 'M',
+        (false, true, _) => 'I',
+        (true, true, _) => 'G',
+    };
+
+    let mut constraint_terms = Vec::new();
+    for c in &constraints {
+        let function = c
+            .function
+            .as_ref()
+            .with_context(|| format!("Function of constraint ({}) is not set", c.id))?;
+        let equality = c.equality();
+        if equality == Equality::Unspecified {
+            bail!("Equality of constraint ({}) is not specified", c.id);
+        }
+        constraint_terms.push((equality, Terms::new(function, &index)?));
+    }
+    let bounds: Vec<(f64, f64)> = decision_variables
+        .iter()
+        .map(|v| {
+            let bound = v.effective_bound();
+            (bound.lower, bound.upper)
+        })
+        .collect();
+    let constraint_type = if constraints.is_empty() {
+        let is_box = bounds
+            .iter()
+            .zip(&variable_types)
+            .any(|((lower, upper), ty)| *ty != 2 && (lower.is_finite() || upper.is_finite()));
+        if is_box {
+            'B'
+        } else {
+            'N'
+        }
+    } else if constraint_terms
+        .iter()
+        .all(|(_, terms)| terms.quadratic.is_empty())
+    {
+        'L'
+    } else if constraint_terms.iter().all(|(equality, terms)| {
+        terms.quadratic.is_empty()
+            || (*equality == Equality::LessThanOrEqualToZero && terms.is_diagonal_convex(1.0))
+    }) {
+        'D'
+    } else if constraint_terms.iter().all(|(equality, terms)| {
+        terms.quadratic.is_empty()
+            || (*equality == Equality::LessThanOrEqualToZero && terms.is_convex(1.0))
+    }) {
+        'C'
+    } else {
+        'Q'
+    };
+
+    let name = instance
+        .description
+        .as_ref()
+        .and_then(|d| d.name.as_deref())
+        .unwrap_or("ommx");
+    writeln!(
+        writer,
+        "{} # problem name",
+        name.replace(char::is_whitespace, "_")
+    )?;
+    writeln!(
+        writer,
+        "{objective_type}{variable_type}{constraint_type} # problem type"
+    )?;
+    match sense {
+        Sense::Minimize => writeln!(writer, "minimize # objective sense")?,
+        _ => writeln!(writer, "maximize # objective sense")?,
+    }
+    writeln!(writer, "{} # number of variables", decision_variables.len())?;
+    if !constraints.is_empty() {
+        writeln!(writer, "{} # number of constraints", constraints.len())?;
+    }
+
+    if objective_type != 'L' {
+        writeln!(
+            writer,
+            "{} # number of quadratic terms in objective",
+            objective.quadratic.len()
+        )?;
+        for ((i, j), value) in &objective.quadratic {
+            writeln!(writer, "{i} {j} {value}")?;
+        }
+    }
+    writeln!(
+        writer,
+        "0 # default value for linear coefficients in objective"
+    )?;
+    writeln!(
+        writer,
+        "{} # number of non-default linear coefficients in objective",
+        objective.linear.len()
+    )?;
+    for (i, value) in &objective.linear {
+        writeln!(writer, "{i} {value}")?;
+    }
+    writeln!(writer, "{} # objective constant", objective.constant)?;
+
+    if !constraints.is_empty() {
+        if constraint_type != 'L' {
+            let quadratic: Vec<_> = constraint_terms
+                .iter()
+                .enumerate()
+                .flat_map(|(n, (_, terms))| {
+                    terms
+                        .quadratic
+                        .iter()
+                        .map(move |((i, j), value)| (n + 1, *i, *j, *value))
+                })
+                .collect();
+            writeln!(
+                writer,
+                "{} # number of quadratic terms in all constraints",
+                quadratic.len()
+            )?;
+            for (n, i, j, value) in quadratic {
+                writeln!(writer, "{n} {i} {j} {value}")?;
+            }
+        }
+        let linear: Vec<_> = constraint_terms
+            .iter()
+            .enumerate()
+            .flat_map(|(n, (_, terms))| {
+                terms
+                    .linear
+                    .iter()
+                    .map(move |(i, value)| (n + 1, *i, *value))
+            })
+            .collect();
+        writeln!(
+            writer,
+            "{} # number of linear terms in all constraints",
+            linear.len()
+        )?;
+        for (n, i, value) in linear {
+            writeln!(writer, "{n} {i} {value}")?;
+        }
+    }
+
+    if !constraints.is_empty() || variable_type != 'B' {
+        writeln!(writer, "{} # value for infinity", number(f64::INFINITY))?;
+    }
+    if !constraints.is_empty() {
+        // `f(x) + c (<= or =) 0` is `-inf (or -c) <= f(x) <= -c`
+        let sides: Vec<(f64, f64)> = constraint_terms
+            .iter()
+            .map(|(equality, terms)| match equality {
+                Equality::EqualToZero => (-terms.constant, -terms.constant),
+                _ => (f64::NEG_INFINITY, -terms.constant),
+            })
+            .collect();
+        write_entries(
+            &mut writer,
+            f64::NEG_INFINITY,
+            sides.iter().map(|(lower, _)| *lower),
+            "left-hand-side",
+        )?;
+        write_entries(
+            &mut writer,
+            0.0,
+            sides.iter().map(|(_, upper)| *upper),
+            "right-hand-side",
+        )?;
+    }
+    if variable_type != 'B' {
+        write_entries(
+            &mut writer,
+            0.0,
+            bounds.iter().map(|(lower, _)| *lower),
+            "variable lower bound",
+        )?;
+        write_entries(
+            &mut writer,
+            f64::INFINITY,
+            bounds.iter().map(|(_, upper)| *upper),
+            "variable upper bound",
+        )?;
+    }
+    if matches!(variable_type, 'M' | 'I' | 'G') {
+        writeln!(writer, "0 # default variable type is continuous")?;
+        let non_default: Vec<_> = variable_types
+            .iter()
+            .enumerate()
+            .filter(|(_, ty)| **ty != 0)
+            .collect();
+        writeln!(
+            writer,
+            "{} # number of non-default variable types",
+            non_default.len()
+        )?;
+        for (i, ty) in non_default {
+            writeln!(writer, "{} {ty}", i + 1)?;
+        }
+    }
+
+    writeln!(
+        writer,
+        "0 # default variable primal value in starting point"
+    )?;
+    writeln!(
+        writer,
+        "0 # number of non-default variable primal values in starting point"
+    )?;
+    if !constraints.is_empty() {
+        writeln!(
+            writer,
+            "0 # default constraint dual value in starting point"
+        )?;
+        writeln!(
+            writer,
+            "0 # number of non-default constraint dual values in starting point"
+        )?;
+    }
+    writeln!(
+        writer,
+        "0 # default variable bound dual value in starting point"
+    )?;
+    writeln!(
+        writer,
+        "0 # number of non-default variable bound dual values in starting point"
+    )?;
+
+    writeln!(
+        writer,
+        "{} # number of non-default variable names",
+        decision_variables.len()
+    )?;
+    for (i, v) in decision_variables.iter().enumerate() {
+        writeln!(writer, "{} x{}", i + 1, v.id)?;
+    }
+    writeln!(
+        writer,
+        "{} # number of non-default constraint names",
+        constraints.len()
+    )?;
+    for (n, c) in constraints.iter().enumerate() {
+        writeln!(writer, "{} c{}", n + 1, c.id)?;
+    }
+    Ok(())
+}
+
+/// Terms of a function in the 1-based indices of decision variables
+struct Terms {
+    /// `(i, j) -> Q_ij` with `i >= j` of `x^T Q x / 2`, i.e. the coefficient of `x_i^2` is doubled
+    quadratic: BTreeMap<(usize, usize), f64>,
+    linear: BTreeMap<usize, f64>,
+    constant: f64,
+}
+
+impl Terms {
+    fn new(function: &Function, index: &BTreeMap<u64, usize>) -> Result<Self> {
+        let get = |id: &u64| {
+            index
+                .get(id)
+                .cloned()
+                .with_context(|| format!("Decision variable ({id}) is used but not defined"))
+        };
+        let mut terms = Terms {
+            quadratic: BTreeMap::new(),
+            linear: BTreeMap::new(),
+            constant: 0.0,
+        };
+        for (ids, coefficient) in Polynomial::from(function.clone()).monomials() {
+            match ids.as_slice() {
+                [] => terms.constant += coefficient,
+                [i] => *terms.linear.entry(get(i)?).or_insert(0.0) += coefficient,
+                [i, j] => {
+                    let (i, j) = (get(i)?, get(j)?);
+                    let value = if i == j {
+                        2.0 * coefficient
+                    } else {
+                        coefficient
+                    };
+                    *terms.quadratic.entry((i.max(j), i.min(j))).or_insert(0.0) += value;
+                }
+                _ => bail!(
+                    "QPLIB does not support a term of degree {}: {ids:?}",
+                    ids.len()
+                ),
+            }
+        }
+        terms.quadratic.retain(|_, value| *value != 0.0);
+        terms.linear.retain(|_, value| *value != 0.0);
+        Ok(terms)
+    }
+
+    /// Check if `sign` times the quadratic part is convex
+    fn is_convex(&self, sign: f64) -> bool {
+        // Back to the coefficients of `x_i x_j`
+        let quad = self
+            .quadratic
+            .iter()
+            .map(|(&(i, j), value)| {
+                let value = if i == j { value / 2.0 } else { *value };
+                ((i as u64, j as u64), sign * value)
+            })
+            .collect();
+        is_hessian_positive_semidefinite(&quad, CONVEXITY_ATOL)
+    }
+
+    /// Test if `sign` times the quadratic part is convex with a diagonal Hessian, i.e. consists of `x_i^2` with non-negative coefficients
+    fn is_diagonal_convex(&self, sign: f64) -> bool {
+        self.quadratic
+            .iter()
+            .all(|(&(i, j), value)| i == j && sign * value >= -CONVEXITY_ATOL)
+    }
+}
+
+/// Write a default value and the entries `i value` different from it, for 1-based `i`
+fn write_entries(
+    writer: &mut impl Write,
+    default: f64,
+    values: impl Iterator<Item = f64>,
+    label: &str,
+) -> Result<()> {
+    let non_default: Vec<_> = values
+        .enumerate()
+        .filter(|(_, value)| *value != default)
+        .collect();
+    writeln!(writer, "{} # default {label} value", number(default))?;
+    writeln!(
+        writer,
+        "{} # number of non-default {label} values",
+        non_default.len()
+    )?;
+    for (i, value) in non_default {
+        writeln!(writer, "{} {}", i + 1, number(value))?;
+    }
+    Ok(())
+}
+
+fn number(value: f64) -> String {
+    if value == f64::INFINITY {
+        INFINITY.to_string()
+    } else if value == f64::NEG_INFINITY {
+        format!("-{INFINITY}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{Bound, Constraint, DecisionVariable, Linear, Quadratic};
+    use maplit::btreemap;
+
+    fn variable(id: u64, kind: Kind, bound: Option<(f64, f64)>) -> DecisionVariable {
+        DecisionVariable {
+            id,
+            kind: kind as i32,
+            bound: bound.map(|(lower, upper)| Bound { lower, upper }),
+            ..Default::default()
+        }
+    }
+
+    fn constraint(id: u64, equality: Equality, function: Function) -> Constraint {
+        Constraint {
+            id,
+            equality: equality as i32,
+            function: Some(function),
+            ..Default::default()
+        }
+    }
+
+    fn write_string(instance: &Instance) -> String {
+        let mut out = Vec::new();
+        write(instance, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn convex_objective_linear_constraints() {
+        // Minimize `x0^2 + x0 x1 + 2 x1^2 + 3 x2 - x3 + 5`
+        // subject to `x0 + 2 x1 - 4 <= 0` and `x2 - x3 + 1 = 0`
+        let instance = Instance {
+            decision_variables: vec![
+                variable(0, Kind::Continuous, Some((-1.0, f64::INFINITY))),
+                variable(1, Kind::Integer, Some((0.0, 10.0))),
+                variable(2, Kind::Binary, None),
+                variable(3, Kind::Continuous, None),
+            ],
+            objective: Some(
+                Quadratic::from_terms(
+                    btreemap! { (0, 0) => 1.0, (0, 1) => 1.0, (1, 1) => 2.0 },
+                    Some(Linear::new([(2, 3.0), (3, -1.0)].into_iter(), 5.0)),
+                )
+                .into(),
+            ),
+            constraints: vec![
+                constraint(
+                    5,
+                    Equality::EqualToZero,
+                    Linear::new([(2, 1.0), (3, -1.0)].into_iter(), 1.0).into(),
+                ),
+                constraint(
+                    0,
+                    Equality::LessThanOrEqualToZero,
+                    Linear::new([(0, 1.0), (1, 2.0)].into_iter(), -4.0).into(),
+                ),
+            ],
+            sense: Sense::Minimize as i32,
+            ..Default::default()
+        };
+        let expected = "\
+ommx # problem name
+CGL # problem type
+minimize # objective sense
+4 # number of variables
+2 # number of constraints
+3 # number of quadratic terms in objective
+1 1 2
+2 1 1
+2 2 4
+0 # default value for linear coefficients in objective
+2 # number of non-default linear coefficients in objective
+3 3
+4 -1
+5 # objective constant
+4 # number of linear terms in all constraints
+1 1 1
+1 2 2
+2 3 1
+2 4 -1
+1.0E+30 # value for infinity
+-1.0E+30 # default left-hand-side value
+1 # number of non-default left-hand-side values
+2 -1
+0 # default right-hand-side value
+2 # number of non-default right-hand-side values
+1 4
+2 -1
+0 # default variable lower bound value
+2 # number of non-default variable lower bound values
+1 -1
+4 -1.0E+30
+1.0E+30 # default variable upper bound value
+2 # number of non-default variable upper bound values
+2 10
+3 1
+0 # default variable type is continuous
+2 # number of non-default variable types
+2 1
+3 2
+0 # default variable primal value in starting point
+0 # number of non-default variable primal values in starting point
+0 # default constraint dual value in starting point
+0 # number of non-default constraint dual values in starting point
+0 # default variable bound dual value in starting point
+0 # number of non-default variable bound dual values in starting point
+4 # number of non-default variable names
+1 x0
+2 x1
+3 x2
+4 x3
+2 # number of non-default constraint names
+1 c0
+2 c5
+";
+        assert_eq!(write_string(&instance), expected);
+    }
+
+    #[test]
+    fn nonconcave_objective_quadratic_constraint() {
+        // Maximize `x0^2 - x1`, which is not concave, subject to the convex `x0^2 + x1^2 - 1 <= 0`
+        let instance = Instance {
+            decision_variables: vec![
+                variable(0, Kind::Continuous, Some((-2.0, 2.0))),
+                variable(1, Kind::Continuous, Some((0.0, f64::INFINITY))),
+            ],
+            objective: Some(
+                Quadratic::from_terms(
+                    btreemap! { (0, 0) => 1.0 },
+                    Some(Linear::new([(1, -1.0)].into_iter(), 0.0)),
+                )
+                .into(),
+            ),
+            constraints: vec![constraint(
+                0,
+                Equality::LessThanOrEqualToZero,
+                Quadratic::from_terms(
+                    btreemap! { (0, 0) => 1.0, (1, 1) => 1.0 },
+                    Some(Linear::new(std::iter::empty(), -1.0)),
+                )
+                .into(),
+            )],
+            sense: Sense::Maximize as i32,
+            ..Default::default()
+        };
+        let expected = "\
+ommx # problem name
+QCD # problem type
+maximize # objective sense
+2 # number of variables
+1 # number of constraints
+1 # number of quadratic terms in objective
+1 1 2
+0 # default value for linear coefficients in objective
+1 # number of non-default linear coefficients in objective
+2 -1
+0 # objective constant
+2 # number of quadratic terms in all constraints
+1 1 1 2
+1 2 2 2
+0 # number of linear terms in all constraints
+1.0E+30 # value for infinity
+-1.0E+30 # default left-hand-side value
+0 # number of non-default left-hand-side values
+0 # default right-hand-side value
+1 # number of non-default right-hand-side values
+1 1
+0 # default variable lower bound value
+1 # number of non-default variable lower bound values
+1 -2
+1.0E+30 # default variable upper bound value
+1 # number of non-default variable upper bound values
+1 2
+0 # default variable primal value in starting point
+0 # number of non-default variable primal values in starting point
+0 # default constraint dual value in starting point
+0 # number of non-default constraint dual values in starting point
+0 # default variable bound dual value in starting point
+0 # number of non-default variable bound dual values in starting point
+2 # number of non-default variable names
+1 x0
+2 x1
+1 # number of non-default constraint names
+1 c0
+";
+        assert_eq!(write_string(&instance), expected);
+    }
+
+    #[test]
+    fn diagonal_objective_type() {
+        let problem_type = |terms, sense: Sense| {
+            let instance = Instance {
+                decision_variables: vec![
+                    variable(0, Kind::Continuous, None),
+                    variable(1, Kind::Continuous, None),
+                ],
+                objective: Some(Quadratic::from_terms(terms, None).into()),
+                sense: sense as i32,
+                ..Default::default()
+            };
+            write_string(&instance).lines().nth(1).unwrap().to_string()
+        };
+        let diagonal = btreemap! { (0, 0) => 1.0, (1, 1) => 2.0 };
+        let negated = btreemap! { (0, 0) => -1.0, (1, 1) => -2.0 };
+        let coupled = btreemap! { (0, 0) => 1.0, (0, 1) => 1.0, (1, 1) => 2.0 };
+        assert_eq!(
+            problem_type(diagonal.clone(), Sense::Minimize),
+            "DCN # problem type"
+        );
+        assert_eq!(
+            problem_type(negated.clone(), Sense::Maximize),
+            "DCN # problem type"
+        );
+        assert_eq!(problem_type(coupled, Sense::Minimize), "CCN # problem type");
+        assert_eq!(
+            problem_type(diagonal, Sense::Maximize),
+            "QCN # problem type"
+        );
+        assert_eq!(problem_type(negated, Sense::Minimize), "QCN # problem type");
+    }
+}
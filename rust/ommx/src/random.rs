@@ -1,7 +1,10 @@
 //! Randomly generate OMMX components for benchmarking and testing
 
 use crate::v1::{self, linear::Term, Constraint, Equality};
-use rand::Rng;
+use anyhow::{ensure, Result};
+use rand::{Rng, SeedableRng};
+
+pub use crate::arbitrary::InstanceParameter;
 
 /// Create a random linear programming (LP) instance in a form of `min c^T x` subject to `Ax = b` and `x >= 0` with continuous variables `x`.
 pub fn random_lp(rng: &mut impl Rng, num_variables: usize, num_constraints: usize) -> v1::Instance {
@@ -38,3 +41,157 @@ pub fn random_lp(rng: &mut impl Rng, num_variables: usize, num_constraints: usiz
 
     instance
 }
+
+/// Create a random instance of the type specified by `parameter`
+pub fn random_instance(rng: &mut impl Rng, parameter: &InstanceParameter) -> v1::Instance {
+    match parameter {
+        InstanceParameter::LP {
+            num_constraints,
+            num_variables,
+        } => random_lp(rng, *num_variables, *num_constraints),
+    }
+}
+
+/// Generate `count` random instances for benchmarking, where the `i`-th instance is generated with the seed `base_seed + i`
+///
+/// The result depends only on the arguments. With the `parallel` feature, the instances are generated across threads,
+/// and the result is the same as the serial one regardless of thread scheduling.
+pub fn generate_suite(
+    parameter: InstanceParameter,
+    count: usize,
+    base_seed: u64,
+) -> Vec<v1::Instance> {
+    let generate = |i: usize| {
+        let mut rng =
+            rand_xoshiro::Xoshiro256StarStar::seed_from_u64(base_seed.wrapping_add(i as u64));
+        random_instance(&mut rng, &parameter)
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = count.div_ceil(threads).max(1);
+        let generate = &generate;
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..count)
+                .step_by(chunk_size)
+                .map(|start| {
+                    let end = (start + chunk_size).min(count);
+                    s.spawn(move || (start..end).map(generate).collect::<Vec<_>>())
+                })
+                .collect();
+            // Join in the order of chunks to keep the `i`-th instance at the `i`-th position
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        })
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    (0..count).map(generate).collect()
+}
+
+/// Absolute value used instead of infinite bounds in [`random_state`]
+pub const UNBOUNDED_STATE_LIMIT: f64 = 1e3;
+
+/// Create a random state of `decision_variables` uniformly in their bounds
+///
+/// Binary and integer variables take integer values, and semi-variables take zero with probability `1/2`.
+/// Missing or infinite sides of bounds are replaced by `-UNBOUNDED_STATE_LIMIT` or `UNBOUNDED_STATE_LIMIT`,
+/// or by the finite side shifted by `UNBOUNDED_STATE_LIMIT` if it lies beyond, e.g. `[5000, inf)` becomes `[5000, 6000]`.
+pub fn random_state(
+    rng: &mut impl Rng,
+    decision_variables: &[v1::DecisionVariable],
+) -> Result<v1::State> {
+    let mut entries = std::collections::HashMap::new();
+    for v in decision_variables {
+        let (is_integer, can_be_zero) = v.kind().to_variable_flags()?;
+        let v1::Bound {
+            mut lower,
+            mut upper,
+        } = v.effective_bound();
+        if lower.is_infinite() {
+            lower = (-UNBOUNDED_STATE_LIMIT).min(upper - UNBOUNDED_STATE_LIMIT);
+        }
+        if upper.is_infinite() {
+            upper = UNBOUNDED_STATE_LIMIT.max(lower + UNBOUNDED_STATE_LIMIT);
+        }
+        if is_integer {
+            lower = lower.ceil();
+            upper = upper.floor();
+        }
+        ensure!(
+            lower <= upper,
+            "Bound of decision variable ({}) is empty: [{lower}, {upper}]",
+            v.id
+        );
+        let value = if can_be_zero && rng.gen_bool(0.5) {
+            0.0
+        } else if is_integer {
+            rng.gen_range(lower as i64..=upper as i64) as f64
+        } else if lower == upper {
+            lower
+        } else {
+            rng.gen_range(lower..upper)
+        };
+        entries.insert(v.id, value);
+    }
+    Ok(entries.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::decision_variable::Kind;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn random_state_in_bounds() {
+        let variable = |id, kind: Kind, lower, upper| v1::DecisionVariable {
+            id,
+            kind: kind as i32,
+            bound: Some(v1::Bound { lower, upper }),
+            ..Default::default()
+        };
+        let decision_variables = vec![
+            variable(0, Kind::Continuous, 2000.0, 3000.0),
+            variable(1, Kind::Integer, 5000.0, f64::INFINITY),
+            variable(2, Kind::Continuous, f64::NEG_INFINITY, -5000.0),
+            variable(3, Kind::Continuous, f64::NEG_INFINITY, f64::INFINITY),
+            variable(4, Kind::SemiInteger, 2000.5, 2001.5),
+            v1::DecisionVariable {
+                id: 5,
+                kind: Kind::Binary as i32,
+                ..Default::default()
+            },
+        ];
+        let expected = [
+            (2000.0, 3000.0),
+            (5000.0, 6000.0),
+            (-6000.0, -5000.0),
+            (-1000.0, 1000.0),
+            (2001.0, 2001.0),
+            (0.0, 1.0),
+        ];
+        let mut rng = Xoshiro256StarStar::seed_from_u64(0);
+        for _ in 0..100 {
+            let state = random_state(&mut rng, &decision_variables).unwrap();
+            for (id, (lower, upper)) in expected.iter().enumerate() {
+                let value = state.entries[&(id as u64)];
+                let is_zero = id == 4 && value == 0.0;
+                assert!(
+                    is_zero || (*lower <= value && value <= *upper),
+                    "{id}: {value}"
+                );
+                if matches!(id, 1 | 4 | 5) {
+                    assert_eq!(value.fract(), 0.0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Test helpers for solver adapters, enabled by the `testing` feature
+//!
+//! The solution returned from a solver is verified by evaluating the instance again with its state,
+//! instead of trusting the objective value or the feasibility reported by the solver.
+
+use crate::{
+    v1::{Instance, Solution},
+    Evaluate,
+};
+use anyhow::{ensure, Context, Result};
+
+/// Verify that the state of `solution` satisfies the bounds and kinds of decision variables, and constraints of `instance` within `atol`
+pub fn verify_solution(instance: &Instance, solution: &Solution, atol: f64) -> Result<()> {
+    let state = solution.state.as_ref().context("State is not set")?;
+    for v in &instance.decision_variables {
+        let value = *state
+            .entries
+            .get(&v.id)
+            .with_context(|| format!("Value of decision variable ({}) is not found", v.id))?;
+        let kind = v.kind();
+        let (is_integer, can_be_zero) = kind.to_variable_flags()?;
+        if can_be_zero && value.abs() <= atol {
+            continue;
+        }
+        if is_integer {
+            ensure!(
+                (value - value.round()).abs() <= atol,
+                "Value of {kind:?} decision variable ({}) is not an integer: {value}",
+                v.id
+            );
+        }
+        let bound = v.effective_bound();
+        ensure!(
+            bound.required_atol_to_contain(value) <= atol,
+            "Value of decision variable ({}) is out of bound [{}, {}]: {value}",
+            v.id,
+            bound.lower,
+            bound.upper
+        );
+    }
+    let (evaluated, _) = instance.evaluate(state)?;
+    for c in &evaluated.evaluated_constraints {
+        let violation = c.required_atol_for_feasibility()?;
+        ensure!(
+            violation <= atol,
+            "Constraint ({}) is violated by {violation}",
+            c.id
+        );
+    }
+    Ok(())
+}
+
+/// Assert that `solution` is feasible for `instance` within `atol`, see [`verify_solution`]
+#[track_caller]
+pub fn assert_solution_feasible(instance: &Instance, solution: &Solution, atol: f64) {
+    if let Err(e) = verify_solution(instance, solution, atol) {
+        panic!("Solution is not feasible: {e:#}");
+    }
+}
+
+/// Assert that the objective value of `solution` evaluated with `instance` is `expected` within `tol`
+///
+/// The objective value stored in `solution` is also checked to be consistent with the evaluated one.
+#[track_caller]
+pub fn assert_objective_within(instance: &Instance, solution: &Solution, expected: f64, tol: f64) {
+    let state = solution.state.as_ref().expect("State is not set");
+    let (evaluated, _) = instance
+        .evaluate(state)
+        .unwrap_or_else(|e| panic!("Failed to evaluate the instance: {e:#}"));
+    assert!(
+        (evaluated.objective - expected).abs() <= tol,
+        "Objective value {} is not {expected} within {tol}",
+        evaluated.objective
+    );
+    assert!(
+        (evaluated.objective - solution.objective).abs() <= tol,
+        "Objective value stored in the solution {} differs from the evaluated one {}",
+        solution.objective,
+        evaluated.objective
+    );
+}